@@ -39,6 +39,9 @@ fn main() -> io::Result<()> {
                         }
                         Some(Signal::User1) => println!("Got user signal 1"),
                         Some(Signal::User2) => println!("Got user signal 2"),
+                        Some(Signal::Hangup) => println!("Got hangup signal"),
+                        Some(Signal::Child) => println!("Got child signal"),
+                        Some(Signal::Realtime(n)) => println!("Got real-time signal {}", n),
                         None => break, // No more signals.
                     }
                 },
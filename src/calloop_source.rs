@@ -0,0 +1,129 @@
+//! Module with [`SignalSource`].
+
+use std::io;
+
+use calloop::generic::Generic;
+use calloop::{EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory};
+
+use crate::{SignalInfo, SignalSet, Signals};
+
+/// Event source for [calloop], calling the callback for each received signal.
+///
+/// This wraps [`Signals`], using the same file descriptor and receive path,
+/// but is driven by a calloop `EventLoop` instead of [`mio::Poll`]. The
+/// callback is called with the [`SignalInfo`] of each received signal.
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation.
+///
+/// This type is only available if the `calloop` feature is enabled.
+///
+/// [calloop]: https://docs.rs/calloop
+///
+/// # Examples
+///
+/// ```
+/// use std::process;
+/// use std::time::Duration;
+///
+/// use calloop::EventLoop;
+/// use mio_signals::{send_signal, Signal, SignalSource};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut event_loop: EventLoop<Vec<Signal>> = EventLoop::try_new()?;
+///
+///     let signals = SignalSource::new(Signal::Interrupt.into())?;
+///     event_loop
+///         .handle()
+///         .insert_source(signals, |info, _, received| received.push(info.signal()))
+///         .map_err(|err| err.error)?;
+///
+///     // Send ourselves a signal.
+///     send_signal(process::id(), Signal::Interrupt)?;
+///
+///     let mut received = Vec::new();
+///     while received.is_empty() {
+///         event_loop.dispatch(Some(Duration::from_secs(1)), &mut received)?;
+///     }
+///     assert_eq!(received, vec![Signal::Interrupt]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SignalSource {
+    inner: Generic<Signals>,
+}
+
+impl SignalSource {
+    /// Create a new signal event source.
+    ///
+    /// See [`Signals::new`].
+    pub fn new(signals: SignalSet) -> io::Result<SignalSource> {
+        Signals::new(signals).map(SignalSource::from)
+    }
+
+    /// Returns a reference to the underlying [`Signals`].
+    pub fn get_ref(&self) -> &Signals {
+        self.inner.get_ref()
+    }
+
+    /// Returns the underlying [`Signals`], unregistering it.
+    pub fn into_inner(self) -> Signals {
+        self.inner.unwrap()
+    }
+}
+
+impl From<Signals> for SignalSource {
+    fn from(signals: Signals) -> SignalSource {
+        SignalSource {
+            inner: Generic::new(signals, Interest::READ, Mode::Level),
+        }
+    }
+}
+
+impl EventSource for SignalSource {
+    type Event = SignalInfo;
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.inner.process_events(readiness, token, |_, signals| {
+            // This is safe because `receive_info` doesn't close or replace the
+            // file descriptor.
+            let signals = unsafe { signals.get_mut() };
+            while let Some(info) = signals.receive_info()? {
+                callback(info, &mut ());
+            }
+            Ok(PostAction::Continue)
+        })
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.inner.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.inner.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.inner.unregister(poll)
+    }
+}
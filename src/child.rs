@@ -0,0 +1,127 @@
+//! Module with [`ChildWatcher`].
+
+use std::io;
+use std::process::ExitStatus;
+
+use mio::{event, Interest, Registry, Token};
+
+use crate::{sys, Signal, Signals};
+
+/// Notification of child processes changing state.
+///
+/// `ChildWatcher` is build on top of [`Signals`], listening for
+/// [`Signal::Child`] (`SIGCHLD`). When it receives the signal it calls
+/// [`waitpid(2)`] (without blocking) to collect the state of all child
+/// processes that terminated, stopped or continued. This means that
+/// terminated child processes are reaped and don't pile up as zombie
+/// processes.
+///
+/// [`waitpid(2)`]: https://man7.org/linux/man-pages/man2/waitpid.2.html
+///
+/// # Notes
+///
+/// Because `ChildWatcher` reaps *all* child processes, calling methods such as
+/// [`Child::wait`] or [`Child::try_wait`] will return an error once the
+/// process has been reaped by `ChildWatcher`.
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation.
+///
+/// [`Child::wait`]: std::process::Child::wait
+/// [`Child::try_wait`]: std::process::Child::try_wait
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::Command;
+///
+/// use mio::{Poll, Events, Interest, Token};
+/// use mio_signals::ChildWatcher;
+///
+/// const CHILD: Token = Token(10);
+///
+/// fn main() -> io::Result<()> {
+///     let mut poll = Poll::new()?;
+///     let mut events = Events::with_capacity(8);
+///
+///     // Create a `ChildWatcher` and register it with our `Poll` instance.
+///     let mut children = ChildWatcher::new()?;
+///     poll.registry().register(&mut children, CHILD, Interest::READABLE)?;
+///
+///     // Start a child process.
+///     let child = Command::new("true").spawn()?;
+///
+///     loop {
+///         poll.poll(&mut events, None)?;
+///
+///         for event in events.iter() {
+///             match event.token() {
+///                 // Because we're using edge triggers (default in Mio) we need
+///                 // to keep calling `receive` until it returns `Ok(None)`.
+///                 CHILD => while let Some((pid, status)) = children.receive()? {
+///                     println!("Child process {} changed state: {}", pid, status);
+///                     if pid == child.id() && status.code().is_some() {
+///                         return Ok(());
+///                     }
+///                 },
+///                 _ => println!("Got unexpected event: {:?}", event),
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ChildWatcher {
+    signals: Signals,
+}
+
+impl ChildWatcher {
+    /// Create a new child process watcher.
+    pub fn new() -> io::Result<ChildWatcher> {
+        Signals::new(Signal::Child.into()).map(|signals| ChildWatcher { signals })
+    }
+
+    /// Receive the state change of a child process, if any.
+    ///
+    /// This returns the process id of the child and its status. For child
+    /// processes that are stopped [`ExitStatus::stopped_signal`] returns the
+    /// signal that stopped the process and for child processes that continued
+    /// [`ExitStatus::continued`] returns `true`. In all other cases the child
+    /// process terminated and has been reaped.
+    ///
+    /// If no child process changed state this returns `Ok(None)`.
+    ///
+    /// [`ExitStatus::stopped_signal`]: std::os::unix::process::ExitStatusExt::stopped_signal
+    /// [`ExitStatus::continued`]: std::os::unix::process::ExitStatusExt::continued
+    pub fn receive(&mut self) -> io::Result<Option<(u32, ExitStatus)>> {
+        // Multiple `SIGCHLD` signals can be merged into one, so we don't use
+        // the signals other than to empty the queue (to reset the readiness).
+        while self.signals.receive()?.is_some() {}
+        sys::wait_child(-1)
+    }
+}
+
+impl event::Source for ChildWatcher {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.signals.deregister(registry)
+    }
+}
@@ -0,0 +1,68 @@
+//! Module with [`CommandExt`].
+
+use std::os::unix::process::CommandExt as _;
+use std::process::Command;
+
+use crate::sys;
+
+/// Extension trait for [`Command`] to reset the signal mask of the spawned
+/// process.
+///
+/// [`Signals`] blocks the signals it receives (on Android and Linux) and the
+/// signal mask is inherited by child processes, also across [`execve(2)`].
+/// This means that a child process spawned after creating `Signals` would
+/// ignore e.g. `SIGINT` (Ctrl+C) and `SIGTERM` (`docker stop`).
+///
+/// The standard library currently resets the signal mask when spawning a
+/// process, but doesn't guarantee it, and signals blocked by [`pre_exec`]
+/// closures are still inherited. [`CommandExt::reset_signal_mask`] ensures the
+/// spawned process starts with an empty signal mask.
+///
+/// Note that there is no option to apply this automatically, `Signals` can't
+/// change how a `Command` spawns a process. Resetting the signal mask in every
+/// forked process (using `pthread_atfork(3)`) would also apply to processes
+/// that don't call `execve(2)`, breaking `Signals` in them.
+///
+/// [`Signals`]: crate::Signals
+/// [`execve(2)`]: https://man7.org/linux/man-pages/man2/execve.2.html
+/// [`pre_exec`]: std::os::unix::process::CommandExt::pre_exec
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::Command;
+///
+/// use mio_signals::{CommandExt, Signal, Signals};
+///
+/// fn main() -> io::Result<()> {
+///     let _signals = Signals::new(Signal::Interrupt | Signal::Terminate)?;
+///
+///     // The child process can be stopped using `SIGINT` and `SIGTERM`, even
+///     // though they're blocked in this process.
+///     let status = Command::new("true").reset_signal_mask().status()?;
+///     assert!(status.success());
+///     Ok(())
+/// }
+/// ```
+pub trait CommandExt {
+    /// Unblock all signals in the spawned process, before it calls
+    /// [`execve(2)`].
+    ///
+    /// This uses [`pre_exec`], see its documentation for the implications.
+    /// Most notably it means the standard library can't use `posix_spawn(3)`
+    /// to spawn the process and falls back to `fork(2)` and `execve(2)`, which
+    /// is slower. The signal mask is reset after the `pre_exec` closures
+    /// registered before this call are run.
+    ///
+    /// [`execve(2)`]: https://man7.org/linux/man-pages/man2/execve.2.html
+    /// [`pre_exec`]: std::os::unix::process::CommandExt::pre_exec
+    fn reset_signal_mask(&mut self) -> &mut Self;
+}
+
+impl CommandExt for Command {
+    fn reset_signal_mask(&mut self) -> &mut Command {
+        // This is safe because `unblock_all_signals` is async-signal-safe.
+        unsafe { self.pre_exec(sys::unblock_all_signals) }
+    }
+}
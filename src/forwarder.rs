@@ -0,0 +1,185 @@
+//! Module with [`SignalForwarder`].
+
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, ExitStatus};
+
+use mio::{event, Interest, Registry, Token};
+
+use crate::{sys, Pgid, Pid, Signal, SignalSet, Signals, Target};
+
+/// Forwards received signals to a child process, or its process group.
+///
+/// `SignalForwarder` is build on top of [`Signals`], listening for the signals
+/// to forward and [`Signal::Child`] (`SIGCHLD`). Every received signal is sent
+/// to the child process (or its process group) using [`send_signal_to`], after
+/// which it checks if the child process exited using [`waitpid(2)`]. This
+/// makes it possible to wrap another program, like a process supervisor (e.g.
+/// [tini]) would, exiting with the exit status of the child process.
+///
+/// [`send_signal_to`]: crate::send_signal_to
+/// [`waitpid(2)`]: https://man7.org/linux/man-pages/man2/waitpid.2.html
+/// [tini]: https://github.com/krallin/tini
+///
+/// # Notes
+///
+/// [`Signal::Child`] is used to detect the exit of the child process, it's
+/// never forwarded.
+///
+/// Only the child process is reaped, once it exited. This means that calling
+/// [`Child::wait`] afterwards returns an error. To reap other child processes
+/// use a [`ChildWatcher`].
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation.
+///
+/// [`Child::wait`]: std::process::Child::wait
+/// [`ChildWatcher`]: crate::ChildWatcher
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::{self, Command};
+///
+/// use mio::{Events, Interest, Poll, Token};
+/// use mio_signals::{send_signal, Signal, SignalForwarder};
+///
+/// const FORWARDER: Token = Token(10);
+///
+/// fn main() -> io::Result<()> {
+///     let mut poll = Poll::new()?;
+///     let mut events = Events::with_capacity(8);
+///
+///     // Start the program we're wrapping and forward the signals to it.
+///     let child = Command::new("sleep").arg("10").spawn()?;
+///     let signals = Signal::Interrupt | Signal::Terminate | Signal::Quit;
+///     let mut forwarder = SignalForwarder::new(&child, signals)?;
+///     poll.registry().register(&mut forwarder, FORWARDER, Interest::READABLE)?;
+///
+///     // Ask ourselves to stop, which is forwarded to the child process.
+///     send_signal(process::id(), Signal::Terminate)?;
+///
+///     loop {
+///         poll.poll(&mut events, None)?;
+///
+///         for event in events.iter() {
+///             match event.token() {
+///                 FORWARDER => if let Some(status) = forwarder.forward()? {
+///                     println!("Child process exited: {}", status);
+///                     // In a real program we would exit with the same status.
+///                     return Ok(());
+///                 },
+///                 _ => println!("Got unexpected event: {:?}", event),
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SignalForwarder {
+    /// Signals to forward and `Signal::Child`.
+    signals: Signals,
+    target: Target,
+    /// Process id of the child process.
+    pid: Pid,
+    /// Exit status of the child process, once it exited.
+    status: Option<ExitStatus>,
+}
+
+impl SignalForwarder {
+    /// Create a new signal forwarder, forwarding `signals` to the `child`
+    /// process.
+    pub fn new(child: &Child, signals: SignalSet) -> io::Result<SignalForwarder> {
+        let pid = Pid::new(child.id())?;
+        SignalForwarder::with_target(Target::Process(pid), pid, signals)
+    }
+
+    /// Create a new signal forwarder, forwarding `signals` to all processes in
+    /// the process group `pgid`.
+    ///
+    /// The leader of the process group, i.e. the process with the same process
+    /// id as `pgid`, must be a child process of the calling process, e.g. one
+    /// spawned using [`CommandExt::process_group`] with `0`. Its exit is
+    /// reported by [`SignalForwarder::forward`].
+    ///
+    /// [`CommandExt::process_group`]: std::os::unix::process::CommandExt::process_group
+    pub fn process_group(pgid: Pgid, signals: SignalSet) -> io::Result<SignalForwarder> {
+        let pid = Pid::new(pgid.id())?;
+        SignalForwarder::with_target(Target::ProcessGroup(pgid), pid, signals)
+    }
+
+    fn with_target(target: Target, pid: Pid, signals: SignalSet) -> io::Result<SignalForwarder> {
+        Signals::new(signals | Signal::Child).map(|signals| SignalForwarder {
+            signals,
+            target,
+            pid,
+            status: None,
+        })
+    }
+
+    /// Forward all received signals, returning the exit status of the child
+    /// process once it exited.
+    ///
+    /// Once the child process exited signals are no longer forwarded and this
+    /// keeps returning its exit status.
+    pub fn forward(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_some() {
+            // Empty the queue (to reset the readiness).
+            while self.signals.receive()?.is_some() {}
+            return Ok(self.status);
+        }
+
+        while let Some(signal) = self.signals.receive()? {
+            if signal != Signal::Child {
+                match sys::send_signal(self.target, signal) {
+                    Ok(()) => {}
+                    // All processes already exited.
+                    Err(ref err) if err.raw_os_error() == Some(libc::ESRCH) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        // NOTE: we always check the child process, as `SIGCHLD` may have been
+        // delivered before `SignalForwarder` was created.
+        while let Some((_, status)) = sys::wait_child(self.pid.id() as libc::pid_t)? {
+            if status.stopped_signal().is_none() && !status.continued() {
+                self.status = Some(status);
+                break;
+            }
+        }
+        Ok(self.status)
+    }
+
+    /// Returns the exit status of the child process, if it exited.
+    ///
+    /// This doesn't check the child process, see [`SignalForwarder::forward`].
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.status
+    }
+}
+
+impl event::Source for SignalForwarder {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.signals.deregister(registry)
+    }
+}
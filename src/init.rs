@@ -0,0 +1,188 @@
+//! Module with [`InitSupervisor`].
+
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, ExitStatus};
+
+use mio::{event, Interest, Registry, Token};
+
+use crate::{sys, Pid, Signal, Signals, Target};
+
+/// Supervisor for processes running as init (process 1), e.g. in a container.
+///
+/// Running as init comes with two responsibilities: the process is the parent
+/// of all orphaned processes, which it must reap, and the kernel ignores all
+/// signals for which it didn't install a signal handler, so e.g. `SIGTERM`
+/// doesn't stop it. `InitSupervisor` handles both for a single main child
+/// process.
+///
+/// `InitSupervisor` is build on top of [`Signals`], listening for the
+/// termination signals ([`Signal::Interrupt`], [`Signal::Terminate`],
+/// [`Signal::Quit`] and [`Signal::Hangup`]) and [`Signal::Child`]. Received
+/// termination signals are forwarded to the main child process, see
+/// [`SignalForwarder`]. All child processes are reaped, including ones that
+/// are adopted, and once the main child process exited its exit status is
+/// returned, which can be used to exit with the same code, see
+/// [`InitSupervisor::exit_code`].
+///
+/// When not running as init [`InitSupervisor::set_subreaper`] can be used to
+/// adopt orphaned processes, e.g. those started by the main child process.
+///
+/// [`SignalForwarder`]: crate::SignalForwarder
+///
+/// # Notes
+///
+/// Because `InitSupervisor` reaps *all* child processes, calling methods such
+/// as [`Child::wait`] or [`Child::try_wait`] will return an error once the
+/// process has been reaped.
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation.
+///
+/// [`Child::wait`]: std::process::Child::wait
+/// [`Child::try_wait`]: std::process::Child::try_wait
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::Command;
+///
+/// use mio::{Events, Interest, Poll, Token};
+/// use mio_signals::InitSupervisor;
+///
+/// const SUPERVISOR: Token = Token(10);
+///
+/// fn main() -> io::Result<()> {
+///     let mut poll = Poll::new()?;
+///     let mut events = Events::with_capacity(8);
+///
+///     let child = Command::new("true").spawn()?;
+///     let mut supervisor = InitSupervisor::new(&child)?;
+///     poll.registry().register(&mut supervisor, SUPERVISOR, Interest::READABLE)?;
+///
+///     loop {
+///         // The child process could have exited before we created the
+///         // supervisor, so we check before polling.
+///         if let Some(status) = supervisor.receive()? {
+///             println!("Main child process exited: {}", status);
+///             assert_eq!(supervisor.exit_code(), Some(0));
+///             // In a real program we would exit with the same exit code.
+///             return Ok(());
+///         }
+///
+///         poll.poll(&mut events, None)?;
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InitSupervisor {
+    /// Termination signals and `Signal::Child`.
+    signals: Signals,
+    /// Process id of the main child process.
+    pid: Pid,
+    /// Exit status of the main child process, once it exited.
+    status: Option<ExitStatus>,
+}
+
+impl InitSupervisor {
+    /// Create a new supervisor for the main `child` process.
+    ///
+    /// Signals that are ignored (`SIG_IGN`) are reset to their default action,
+    /// e.g. `SIGHUP` when started using `nohup(1)`, so they can still be
+    /// forwarded. See [`SignalsBuilder::reset_ignored`].
+    ///
+    /// [`SignalsBuilder::reset_ignored`]: crate::SignalsBuilder::reset_ignored
+    pub fn new(child: &Child) -> io::Result<InitSupervisor> {
+        let signals =
+            Signal::Interrupt | Signal::Terminate | Signal::Quit | Signal::Hangup | Signal::Child;
+        let pid = Pid::new(child.id())?;
+        Signals::builder(signals)
+            .reset_ignored(true)
+            .build()
+            .map(|signals| InitSupervisor {
+                signals,
+                pid,
+                status: None,
+            })
+    }
+
+    /// Make the calling process a child subreaper, using
+    /// `PR_SET_CHILD_SUBREAPER` (see [`prctl(2)`]).
+    ///
+    /// Orphaned processes are normally adopted by init (process 1). A child
+    /// subreaper adopts the orphaned processes that descend from it instead,
+    /// e.g. the processes started by the main child process that outlive it.
+    /// This should be called before spawning the main child process.
+    ///
+    /// This is only supported on Android and Linux, on other platforms this
+    /// returns an error of kind `Unsupported`.
+    ///
+    /// [`prctl(2)`]: https://man7.org/linux/man-pages/man2/prctl.2.html
+    pub fn set_subreaper() -> io::Result<()> {
+        sys::set_child_subreaper()
+    }
+
+    /// Forward all received termination signals to the main child process and
+    /// reap all child processes that terminated, returning the exit status of
+    /// the main child process once it exited.
+    ///
+    /// Once the main child process exited signals are no longer forwarded and
+    /// this keeps returning its exit status, while still reaping the other
+    /// child processes.
+    pub fn receive(&mut self) -> io::Result<Option<ExitStatus>> {
+        while let Some(signal) = self.signals.receive()? {
+            if signal != Signal::Child && self.status.is_none() {
+                match sys::send_signal(Target::Process(self.pid), signal) {
+                    Ok(()) => {}
+                    // Main child process already exited.
+                    Err(ref err) if err.raw_os_error() == Some(libc::ESRCH) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        // NOTE: we always reap the child processes, as `SIGCHLD` may have been
+        // delivered before `InitSupervisor` was created.
+        while let Some((pid, status)) = sys::wait_child(-1)? {
+            if pid == self.pid.id() && status.stopped_signal().is_none() && !status.continued() {
+                self.status = Some(status);
+            }
+        }
+        Ok(self.status)
+    }
+
+    /// Returns the exit code to mirror the exit status of the main child
+    /// process, if it exited.
+    ///
+    /// If the main child process was terminated by a signal this returns `128`
+    /// plus the signal number, like shells do.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.status
+            .and_then(|status| status.code().or_else(|| status.signal().map(|s| 128 + s)))
+    }
+}
+
+impl event::Source for InitSupervisor {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.signals.deregister(registry)
+    }
+}
@@ -19,6 +19,15 @@
 //! a port to Windows please see [issue #4].
 //!
 //! [issue #4]: https://github.com/Thomasdezeeuw/mio-signals/issues/4
+//!
+//! ## Features
+//!
+//! The following optional features are available:
+//!
+//! * `calloop`: enables [`SignalSource`], a calloop event source.
+//! * `futures`: enables [`SignalStream`], a `Stream` of signals usable with any
+//!   executor.
+//! * `tokio`: enables [`AsyncSignals`], to receive signals using Tokio.
 
 // TODO: #[non_exhaustive] to `Signal`.
 
@@ -36,15 +45,50 @@
 #![doc(test(attr(deny(warnings))))]
 // `SignalSet` can never be empty, thus an `is_empty` method doesn't make sense.
 #![allow(clippy::len_without_is_empty)]
+// `io::Error::other` and `div_ceil` require a newer Rust version than we
+// support.
+#![allow(clippy::io_other_error, clippy::manual_div_ceil)]
 
 use std::iter::FusedIterator;
-use std::num::NonZeroU8;
+use std::num::NonZeroU128;
 use std::ops::BitOr;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::time::{Duration, Instant};
 use std::{fmt, io};
 
+use log::{log_enabled, warn, Level};
 use mio::{event, Interest, Registry, Token};
 
+#[cfg(feature = "calloop")]
+mod calloop_source;
+mod child;
+mod command;
+mod forwarder;
+mod init;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod process;
+mod shutdown;
+#[cfg(feature = "futures")]
+mod signal_stream;
 mod sys;
+mod target;
+#[cfg(feature = "tokio")]
+mod tokio_signals;
+
+#[cfg(feature = "calloop")]
+pub use calloop_source::SignalSource;
+pub use child::ChildWatcher;
+pub use command::CommandExt;
+pub use forwarder::SignalForwarder;
+pub use init::InitSupervisor;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use process::{ProcessExit, ProcessHandle};
+pub use shutdown::{Shutdown, ShutdownHandle, ShutdownState};
+#[cfg(feature = "futures")]
+pub use signal_stream::SignalStream;
+pub use target::{Pgid, Pid, Target};
+#[cfg(feature = "tokio")]
+pub use tokio_signals::AsyncSignals;
 
 /// Notification of process signals.
 ///
@@ -59,6 +103,10 @@ mod sys;
 /// Any threads spawned before calling `Signals::new` will experience the
 /// default process signals behaviour, i.e. sending it a signal will stop it.
 ///
+/// If that is not possible, e.g. because libraries spawn threads before `main`
+/// is called, use [`Signals::new_all_threads`] or [`Signals::new_self_pipe`]
+/// instead.
+///
 /// # Notes
 ///
 /// On Android and Linux this will block all signals in the signal set given
@@ -71,6 +119,14 @@ mod sys;
 /// Same as on Linux based systems; the program is not interrupted, or in any way
 /// notified of signal until the assiocated [`Poll`] is [polled].
 ///
+/// When dropped the signal mask or signal handler action is restored to what it
+/// was before. Multiple `Signals` can use the same signal, in which case the
+/// signal mask or action is only restored once the last `Signals` using the
+/// signal is dropped.
+///
+/// The signal mask is inherited by child processes, use
+/// [`CommandExt::reset_signal_mask`] to reset it when spawning a process.
+///
 /// [`pthread_sigmask(3)`]: https://man7.org/linux/man-pages/man3/pthread_sigmask.3.html
 /// [`Poll`]: mio::Poll
 /// [polled]: mio::Poll::poll
@@ -121,6 +177,9 @@ mod sys;
 ///                         Some(Signal::Quit) => println!("Got quit signal"),
 ///                         Some(Signal::User1) => println!("Got user signal 1"),
 ///                         Some(Signal::User2) => println!("Got user signal 2"),
+///                         Some(Signal::Hangup) => println!("Got hangup signal"),
+///                         Some(Signal::Child) => println!("Got child signal"),
+///                         Some(Signal::Realtime(n)) => println!("Got real-time signal {}", n),
 ///                         None => break,
 ///                     }
 ///                 },
@@ -134,19 +193,412 @@ mod sys;
 #[derive(Debug)]
 pub struct Signals {
     sys: sys::Signals,
+    /// Whether or not to return the information about signals, other than
+    /// the signal itself, see [`SignalsBuilder::signal_info`].
+    signal_info: bool,
 }
 
 impl Signals {
     /// Create a new signal notifier.
+    ///
+    /// If warnings are logged (using the [`log`] crate) this logs a warning
+    /// for every thread that doesn't block the signals, see
+    /// [`Signals::check_thread_masks`].
+    ///
+    /// [`log`]: https://docs.rs/log
+    ///
+    /// On Android and Linux this returns an error if any of the signals is
+    /// ignored (`SIG_IGN`), as the kernel discards ignored signals. Ignored
+    /// signals are often inherited from the parent process, e.g. `nohup(1)`
+    /// ignores `SIGHUP`. Use [`SignalsBuilder::reset_ignored`] to reset them to
+    /// the default action instead.
+    ///
+    /// This is a shortcut for creating `Signals` using [`SignalsBuilder`] with
+    /// the default settings.
     pub fn new(signals: SignalSet) -> io::Result<Signals> {
-        sys::Signals::new(signals).map(|sys| Signals { sys })
+        SignalsBuilder::new(signals).build()
+    }
+
+    /// Create a new [`SignalsBuilder`] to configure `Signals`.
+    ///
+    /// See [`SignalsBuilder::new`].
+    pub const fn builder(signals: SignalSet) -> SignalsBuilder {
+        SignalsBuilder::new(signals)
+    }
+
+    /// Create a new signal notifier in fan-out mode.
+    ///
+    /// Normally a signal is only received by a single `Signals`, even if
+    /// multiple `Signals` include the signal. In fan-out mode every `Signals`
+    /// (also created using `new_fan_out`) that includes the signal receives its
+    /// own copy of it, each with its own readiness notification. This allows
+    /// independent components to each observe the same signal.
+    ///
+    /// Note that signals received by a `Signals` created using [`Signals::new`]
+    /// are not received by `Signals` in fan-out mode. Like `Signals::new` this
+    /// returns an error if any of the signals is ignored.
+    ///
+    /// Like the kernel does, multiple copies of a standard signal that aren't
+    /// received yet are merged into one. At most 1024 signals are kept per
+    /// `Signals`, any signals received after that are dropped until some are
+    /// received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mio_signals::{Signal, Signals};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// // Two independent components both want to know about the terminate
+    /// // signal.
+    /// let mut signals1 = Signals::new_fan_out(Signal::Terminate.into())?;
+    /// let mut signals2 = Signals::new_fan_out(Signal::Terminate.into())?;
+    ///
+    /// // No signals yet.
+    /// assert_eq!(signals1.receive()?, None);
+    /// assert_eq!(signals2.receive()?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_fan_out(signals: SignalSet) -> io::Result<Signals> {
+        let signals = sys::check_signals(signals)
+            .and_then(|()| sys::Signals::new_fan_out(signals))
+            .map(|sys| Signals {
+                sys,
+                signal_info: true,
+            })?;
+        signals.warn_unblocked_threads();
+        Ok(signals)
+    }
+
+    /// Create a new signal notifier, blocking the signals in all existing
+    /// threads.
+    ///
+    /// [`Signals::new`] only blocks the signals in the current thread (and
+    /// threads spawned by it afterwards), which means it must be created
+    /// before any other threads are spawned, see [Multithreaded process].
+    /// This function also blocks the signals in all threads that already
+    /// exist, e.g. those of a runtime's thread pool.
+    ///
+    /// On Android and Linux the threads are found using `/proc/self/task`.
+    /// Every thread is sent a helper signal, using [`tgkill(2)`], whose signal
+    /// handler blocks the signals in that thread. The helper signal is the
+    /// last real-time signal not in `signals` that isn't used by another
+    /// `Signals` and doesn't have a signal handler, its signal action is
+    /// restored once all threads blocked the signals. If a thread doesn't handle the
+    /// helper signal in time, e.g. because it blocks the helper signal, an
+    /// error of kind `TimedOut` is returned. This is only supported on the
+    /// aarch64, arm, x86 and x86_64 architectures, on others an error of kind
+    /// `Unsupported` is returned.
+    ///
+    /// On platforms that use [`kqueue(2)`] the signal action is process-wide,
+    /// so this is the same as [`Signals::new`].
+    ///
+    /// # Notes
+    ///
+    /// When dropped the signals are only unblocked in the current thread,
+    /// other threads keep blocking them. On error the signals might also
+    /// remain blocked in some threads.
+    ///
+    /// [Multithreaded process]: Signals#multithreaded-process
+    /// [`tgkill(2)`]: https://man7.org/linux/man-pages/man2/tgkill.2.html
+    /// [`kqueue(2)`]: https://www.freebsd.org/cgi/man.cgi?query=kqueue&sektion=2
+    ///
+    /// This is a shortcut for using [`SignalsBuilder`] with
+    /// [`Blocking::Process`].
+    pub fn new_all_threads(signals: SignalSet) -> io::Result<Signals> {
+        SignalsBuilder::new(signals)
+            .blocking(Blocking::Process)
+            .build()
+    }
+
+    /// Create a new signal notifier using a self-pipe.
+    ///
+    /// Unlike [`Signals::new`] this doesn't depend on the signal mask of the
+    /// process' threads, so it can be created at any time, even after other
+    /// threads are spawned (e.g. by libraries). It installs a signal handler
+    /// using [`sigaction(2)`] with `SA_SIGINFO`, which writes the received
+    /// signals into a non-blocking pipe that is registered with [`Poll`].
+    ///
+    /// Because the signal handler is process-wide, signals are received no
+    /// matter which thread they are delivered to, as long as at least one
+    /// thread doesn't block the signal. Every `Signals` created using this
+    /// function receives its own copy of each signal, like in fan-out mode.
+    /// If the pipe is full (i.e. signals are not received) further signals are
+    /// dropped.
+    ///
+    /// On all platforms [`SignalInfo`] includes the sending process' id, user
+    /// id and [code], the value for [`SignalCode::Queue`] and the status for
+    /// [`Signal::Child`].
+    ///
+    /// When the last `Signals` using a signal is dropped the signal action is
+    /// restored to what it was before.
+    ///
+    /// # Notes
+    ///
+    /// The self-pipe and the default implementation must not be combined for
+    /// the same signal, as they would overwrite each other's signal mask and
+    /// signal action.
+    ///
+    /// This is a shortcut for using [`SignalsBuilder`] with
+    /// [`Backend::SelfPipe`].
+    ///
+    /// [`sigaction(2)`]: https://man7.org/linux/man-pages/man2/sigaction.2.html
+    /// [`Poll`]: mio::Poll
+    /// [code]: SignalInfo::code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{io, process, thread};
+    ///
+    /// use mio_signals::{send_signal, Signal, Signals};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     // Threads spawned before creating `Signals` don't block the signal,
+    ///     // which is fine when using a self-pipe.
+    ///     let handle = thread::spawn(|| { /* Some work. */ });
+    ///
+    ///     let mut signals = Signals::new_self_pipe(Signal::User1.into())?;
+    ///
+    ///     send_signal(process::id(), Signal::User1)?;
+    ///     assert_eq!(signals.wait()?, Signal::User1);
+    ///
+    ///     # handle.join().unwrap();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_self_pipe(signals: SignalSet) -> io::Result<Signals> {
+        SignalsBuilder::new(signals)
+            .backend(Backend::SelfPipe)
+            .build()
     }
 
     /// Receive a signal, if any.
     ///
     /// If no signal is available this returns `Ok(None)`.
     pub fn receive(&mut self) -> io::Result<Option<Signal>> {
-        self.sys.receive()
+        self.receive_info().map(|info| info.map(|info| info.signal))
+    }
+
+    /// Receive a signal, including information about it, if any.
+    ///
+    /// See [`SignalInfo`] for the information that is available.
+    ///
+    /// If no signal is available this returns `Ok(None)`.
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
+        let signal_info = self.signal_info;
+        self.sys.receive_info().map(|info| {
+            info.map(|info| {
+                if signal_info {
+                    info
+                } else {
+                    SignalInfo::new(info.signal)
+                }
+            })
+        })
+    }
+
+    /// Receive multiple signals, including information about them, at once.
+    ///
+    /// This receives as many signals as fit in `infos` (up to an internal
+    /// limit) using a single system call, returning the number of signals
+    /// received. This reduces the number of system calls when receiving many
+    /// signals, e.g. a burst of [real-time signals].
+    ///
+    /// If no signal is available this returns `Ok(0)`. Just like
+    /// [`Signals::receive`] this should be called until it returns `Ok(0)`.
+    ///
+    /// [real-time signals]: Signal::Realtime
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use mio_signals::{Signal, SignalInfo, Signals, SignalSet};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut signals = Signals::new(SignalSet::all())?;
+    ///
+    ///     // The values in the buffer are overwritten by `receive_batch`.
+    ///     let mut infos = [SignalInfo::new(Signal::Interrupt); 16];
+    ///     loop {
+    ///         let n = signals.receive_batch(&mut infos)?;
+    ///         if n == 0 {
+    ///             break;
+    ///         }
+    ///         for info in &infos[..n] {
+    ///             println!("Got signal {:?}", info.signal());
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        if infos.is_empty() {
+            return Ok(0);
+        }
+
+        let n = self.sys.receive_batch(infos)?;
+        if !self.signal_info {
+            for info in &mut infos[..n] {
+                *info = SignalInfo::new(info.signal);
+            }
+        }
+        Ok(n)
+    }
+
+    /// Returns an iterator that receives all available signals.
+    ///
+    /// The iterator stops once no more signals are available, or after
+    /// returning an error. Internally this uses [`Signals::receive_batch`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use mio_signals::{Signals, SignalSet};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut signals = Signals::new(SignalSet::all())?;
+    ///
+    ///     for info in signals.drain() {
+    ///         println!("Got signal {:?}", info?.signal());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_> {
+        Drain {
+            signals: self,
+            infos: [SignalInfo::new(Signal::Interrupt); DRAIN_SIZE],
+            pos: 0,
+            len: 0,
+            done: false,
+        }
+    }
+
+    /// Wait for a signal, blocking the current thread until one is received.
+    ///
+    /// This is useful for simple programs that don't use [`mio::Poll`], e.g. a
+    /// command line tool that waits for the user to press Ctrl+C.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{io, process};
+    ///
+    /// use mio_signals::{send_signal, Signal, Signals};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut signals = Signals::new(Signal::Interrupt.into())?;
+    ///     # // Don't want to wait for ever.
+    ///     # send_signal(process::id(), Signal::Interrupt)?;
+    ///
+    ///     // Block until the user presses Ctrl+C.
+    ///     let signal = signals.wait()?;
+    ///     assert_eq!(signal, Signal::Interrupt);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn wait(&mut self) -> io::Result<Signal> {
+        loop {
+            match self.receive()? {
+                Some(signal) => return Ok(signal),
+                None => match self.sys.wait(None) {
+                    Ok(_) => continue,
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                },
+            }
+        }
+    }
+
+    /// Wait for a signal, blocking the current thread until one is received or
+    /// `timeout` elapsed.
+    ///
+    /// If no signal is received within `timeout` this returns `Ok(None)`.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<Signal>> {
+        // If the deadline can't be represented we wait without a timeout.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if let Some(signal) = self.receive()? {
+                return Ok(Some(signal));
+            }
+
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            match self.sys.wait(timeout) {
+                Ok(true) => continue,
+                // Timeout elapsed, but check one last time.
+                Ok(false) => return self.receive(),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Check the signal mask of all threads in the process, returning the
+    /// threads that don't block all signals of this `Signals`.
+    ///
+    /// On Android and Linux signals are blocked per thread (see
+    /// [Multithreaded process]). If a signal is sent to the process it can be
+    /// delivered to any thread that doesn't block it, where it's handled by the
+    /// default signal action (e.g. stopping the process) rather than being
+    /// received by `Signals`. This can be used to find such threads, e.g.
+    /// threads spawned before `Signals` was created. The signal masks are read
+    /// from `SigBlk` in `/proc/self/task/[tid]/status`, see [`proc(5)`].
+    ///
+    /// On other platforms, or when using [`Signals::new_self_pipe`], the signal
+    /// mask doesn't matter and this always returns an empty list.
+    ///
+    /// [Multithreaded process]: Signals#multithreaded-process
+    /// [`proc(5)`]: https://man7.org/linux/man-pages/man5/proc.5.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use mio_signals::{Signal, Signals};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let signals = Signals::new(Signal::Terminate.into())?;
+    ///
+    ///     for thread in signals.check_thread_masks()? {
+    ///         println!(
+    ///             "thread {} ({}) doesn't block signals: {:?}",
+    ///             thread.tid(),
+    ///             thread.name(),
+    ///             thread.signals()
+    ///         );
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn check_thread_masks(&self) -> io::Result<Vec<UnblockedThread>> {
+        self.sys.check_thread_masks()
+    }
+
+    /// Log a warning for all threads that don't block the signals.
+    fn warn_unblocked_threads(&self) {
+        if !log_enabled!(Level::Warn) {
+            return;
+        }
+
+        match self.check_thread_masks() {
+            Ok(threads) => {
+                for thread in threads {
+                    warn!(
+                        "thread {} ({}) doesn't block signals {:?}, these signals can stop the process if delivered to this thread; create `Signals` before spawning threads",
+                        thread.tid, thread.name, thread.signals
+                    );
+                }
+            }
+            Err(err) => warn!("error checking signal masks of threads: {}", err),
+        }
     }
 }
 
@@ -174,6 +626,263 @@ impl event::Source for Signals {
     }
 }
 
+/// The file descriptor can be used to integrate `Signals` with other event
+/// loops. It becomes readable once a signal is available to
+/// [`Signals::receive`], but it must not be used to read signals directly.
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+/// See the [`AsRawFd`] implementation.
+impl AsFd for Signals {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // This is safe because the file descriptor is valid for as long as
+        // `Signals` is alive.
+        unsafe { BorrowedFd::borrow_raw(self.sys.as_raw_fd()) }
+    }
+}
+
+/// Number of signals [`Drain`] receives at once.
+const DRAIN_SIZE: usize = 16;
+
+/// Iterator that receives all available signals.
+///
+/// Created by [`Signals::drain`].
+#[derive(Debug)]
+pub struct Drain<'a> {
+    signals: &'a mut Signals,
+    infos: [SignalInfo; DRAIN_SIZE],
+    /// Position in `infos` of the next signal to return.
+    pos: usize,
+    /// Number of received signals in `infos`.
+    len: usize,
+    /// Whether no more signals are available or an error was returned.
+    done: bool,
+}
+
+impl<'a> Iterator for Drain<'a> {
+    type Item = io::Result<SignalInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.len {
+            if self.done {
+                return None;
+            }
+
+            match self.signals.receive_batch(&mut self.infos) {
+                Ok(0) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(n) => {
+                    self.pos = 0;
+                    self.len = n;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        let info = self.infos[self.pos];
+        self.pos += 1;
+        Some(Ok(info))
+    }
+}
+
+impl<'a> FusedIterator for Drain<'a> {}
+
+/// Builder for [`Signals`], to configure its backend and behaviour.
+///
+/// [`Signals::new`] is a shortcut for using the default settings:
+///
+/// * [`Backend::Native`],
+/// * restoring the signal mask or action on drop,
+/// * not resetting ignored signals,
+/// * [`Blocking::Thread`], and
+/// * capturing [`SignalInfo`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use mio_signals::{Backend, Signal, SignalsBuilder};
+///
+/// fn main() -> io::Result<()> {
+///     let mut signals = SignalsBuilder::new(Signal::Terminate | Signal::Interrupt)
+///         .backend(Backend::SelfPipe)
+///         .reset_ignored(true)
+///         .signal_info(false)
+///         .build()?;
+///     assert_eq!(signals.receive()?, None);
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct SignalsBuilder {
+    signals: SignalSet,
+    backend: Backend,
+    restore_on_drop: bool,
+    reset_ignored: bool,
+    blocking: Blocking,
+    signal_info: bool,
+}
+
+impl SignalsBuilder {
+    /// Create a new builder for `Signals` listening for `signals`, using the
+    /// default settings.
+    pub const fn new(signals: SignalSet) -> SignalsBuilder {
+        SignalsBuilder {
+            signals,
+            backend: Backend::Native,
+            restore_on_drop: true,
+            reset_ignored: false,
+            blocking: Blocking::Thread,
+            signal_info: true,
+        }
+    }
+
+    /// Set the backend to use, defaults to [`Backend::Native`].
+    pub const fn backend(mut self, backend: Backend) -> SignalsBuilder {
+        self.backend = backend;
+        self
+    }
+
+    /// Whether or not to restore the signal mask or signal action of the
+    /// signals when the last `Signals` using them is dropped, defaults to
+    /// `true`. Whether or not the signals are restored is determined by the
+    /// `Signals` dropped last.
+    ///
+    /// Note that for [`Backend::SelfPipe`] this means the signals are
+    /// discarded once `Signals` is dropped.
+    pub const fn restore_on_drop(mut self, restore: bool) -> SignalsBuilder {
+        self.restore_on_drop = restore;
+        self
+    }
+
+    /// Whether or not to reset the signal action of signals that are ignored
+    /// (`SIG_IGN`) to the default action (`SIG_DFL`), defaults to `false`.
+    ///
+    /// Ignored signals are discarded by the kernel, so they can't be received
+    /// by [`Backend::Native`] (on Android and Linux) or [`Backend::Thread`].
+    /// Processes can inherit ignored signals from their parent process, e.g.
+    /// `nohup(1)` ignores `SIGHUP`. If this is `false` creating `Signals` using
+    /// those backends returns an error if any of the signals is ignored. The
+    /// other backends can receive ignored signals, for them this has no
+    /// effect.
+    pub const fn reset_ignored(mut self, reset: bool) -> SignalsBuilder {
+        self.reset_ignored = reset;
+        self
+    }
+
+    /// Set in which threads the signals are blocked, defaults to
+    /// [`Blocking::Thread`].
+    pub const fn blocking(mut self, blocking: Blocking) -> SignalsBuilder {
+        self.blocking = blocking;
+        self
+    }
+
+    /// Whether or not to capture information about received signals, other
+    /// than the signal itself, defaults to `true`.
+    ///
+    /// If disabled all methods of [`SignalInfo`], except
+    /// [`SignalInfo::signal`], return `None`.
+    pub const fn signal_info(mut self, capture: bool) -> SignalsBuilder {
+        self.signal_info = capture;
+        self
+    }
+
+    /// Create `Signals` using the settings of this builder.
+    ///
+    /// If warnings are logged this logs a warning for every thread that
+    /// doesn't block the signals, see [`Signals::new`].
+    pub fn build(self) -> io::Result<Signals> {
+        let signals = sys::check_signals(self.signals)
+            .and_then(|()| sys::Signals::build(&self))
+            .map(|sys| Signals {
+                sys,
+                signal_info: self.signal_info,
+            })?;
+        signals.warn_unblocked_threads();
+        Ok(signals)
+    }
+}
+
+/// Backend used by [`Signals`] to receive signals, see
+/// [`SignalsBuilder::backend`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Backend {
+    /// Platform specific implementation: [`signalfd(2)`] on Android and Linux,
+    /// [`kqueue(2)`] on other platforms. See [`Signals`].
+    ///
+    /// [`signalfd(2)`]: https://man7.org/linux/man-pages/man2/signalfd.2.html
+    /// [`kqueue(2)`]: https://www.freebsd.org/cgi/man.cgi?query=kqueue&sektion=2
+    Native,
+    /// Signal handler writing to a pipe, see [`Signals::new_self_pipe`].
+    SelfPipe,
+    /// Thread waiting for signals using [`sigwaitinfo(2)`].
+    ///
+    /// The signals are blocked, like when using [`Backend::Native`], and a
+    /// thread is spawned to wait for them. Received signals are passed to
+    /// `Signals` using an [`eventfd(2)`]. This is only supported on Android
+    /// and Linux, on other platforms creating `Signals` returns an error of
+    /// kind `Unsupported`.
+    ///
+    /// [`sigwaitinfo(2)`]: https://man7.org/linux/man-pages/man2/sigwaitinfo.2.html
+    /// [`eventfd(2)`]: https://man7.org/linux/man-pages/man2/eventfd.2.html
+    Thread,
+}
+
+/// In which threads [`Signals`] blocks the signals, see
+/// [`SignalsBuilder::blocking`].
+///
+/// This only applies to [`Backend::Native`] on Android and Linux and to
+/// [`Backend::Thread`]. The other backends don't block signals.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Blocking {
+    /// Block the signals in the current thread only, threads spawned
+    /// afterwards inherit the blocked signals. See [Multithreaded process].
+    ///
+    /// [Multithreaded process]: Signals#multithreaded-process
+    Thread,
+    /// Block the signals in all threads of the process, see
+    /// [`Signals::new_all_threads`].
+    Process,
+}
+
+/// Thread that doesn't block all signals of a [`Signals`].
+///
+/// Returned by [`Signals::check_thread_masks`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnblockedThread {
+    tid: u32,
+    name: String,
+    signals: SignalSet,
+}
+
+impl UnblockedThread {
+    /// Thread id, see [`gettid(2)`].
+    ///
+    /// [`gettid(2)`]: https://man7.org/linux/man-pages/man2/gettid.2.html
+    pub const fn tid(&self) -> u32 {
+        self.tid
+    }
+
+    /// Name of the thread.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The signals the thread doesn't block.
+    pub const fn signals(&self) -> SignalSet {
+        self.signals
+    }
+}
+
 /// Set of [`Signal`]s used in registering signal notifications with [`Signals`].
 ///
 /// # Examples
@@ -191,19 +900,41 @@ impl event::Source for Signals {
 /// assert!(set.contains(Signal::Interrupt | Signal::Quit));
 /// ```
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
-pub struct SignalSet(NonZeroU8);
+pub struct SignalSet(NonZeroU128);
 
 // NOTE: these may never be zero.
-const INTERRUPT: u8 = 1;
-const QUIT: u8 = 1 << 1;
-const TERMINATE: u8 = 1 << 2;
-const USER1: u8 = 1 << 3;
-const USER2: u8 = 1 << 4;
+const INTERRUPT: u128 = 1;
+const QUIT: u128 = 1 << 1;
+const TERMINATE: u128 = 1 << 2;
+const USER1: u128 = 1 << 3;
+const USER2: u128 = 1 << 4;
+const HANGUP: u128 = 1 << 5;
+const CHILD: u128 = 1 << 6;
+
+/// Bit of `Signal::Realtime(0)`, all bits before it are reserved for the named
+/// signals.
+const REALTIME_SHIFT: u32 = 8;
+/// Maximum offset (exclusive) of `Signal::Realtime` that fits in `SignalSet`.
+const REALTIME_MAX: u8 = (128 - REALTIME_SHIFT) as u8;
 
 impl SignalSet {
     /// Create a new set with all signals.
+    ///
+    /// # Notes
+    ///
+    /// This doesn't include [`Signal::Child`] or any [real-time signals].
+    /// Blocking `SIGCHLD` changes how child processes are reaped, so it needs
+    /// to be added explicitly.
+    ///
+    /// This does include [`Signal::Hangup`], which is ignored when running
+    /// under `nohup(1)`. See [`SignalsBuilder::reset_ignored`] to still receive
+    /// it in that case.
+    ///
+    /// [real-time signals]: Signal::Realtime
     pub const fn all() -> SignalSet {
-        SignalSet(unsafe { NonZeroU8::new_unchecked(INTERRUPT | QUIT | TERMINATE | USER1 | USER2) })
+        SignalSet(unsafe {
+            NonZeroU128::new_unchecked(INTERRUPT | QUIT | TERMINATE | USER1 | USER2 | HANGUP)
+        })
     }
 
     /// Number of signals in the set.
@@ -240,12 +971,17 @@ impl SignalSet {
 impl From<Signal> for SignalSet {
     fn from(signal: Signal) -> Self {
         SignalSet(unsafe {
-            NonZeroU8::new_unchecked(match signal {
+            NonZeroU128::new_unchecked(match signal {
                 Signal::Interrupt => INTERRUPT,
                 Signal::Quit => QUIT,
                 Signal::Terminate => TERMINATE,
                 Signal::User1 => USER1,
                 Signal::User2 => USER2,
+                Signal::Hangup => HANGUP,
+                Signal::Child => CHILD,
+                // Offsets that don't fit are stored as the largest offset, which
+                // is invalid on all platforms and is rejected by `Signals::new`.
+                Signal::Realtime(n) => 1 << (REALTIME_SHIFT + u32::from(n.min(REALTIME_MAX - 1))),
             })
         })
     }
@@ -255,7 +991,7 @@ impl BitOr for SignalSet {
     type Output = SignalSet;
 
     fn bitor(self, rhs: Self) -> Self {
-        SignalSet(unsafe { NonZeroU8::new_unchecked(self.0.get() | rhs.0.get()) })
+        SignalSet(unsafe { NonZeroU128::new_unchecked(self.0.get() | rhs.0.get()) })
     }
 }
 
@@ -287,26 +1023,27 @@ impl fmt::Debug for SignalSet {
 /// # Notes
 ///
 /// The order in which the signals are iterated over is undefined.
-pub struct SignalSetIter(u8);
+pub struct SignalSetIter(u128);
 
 impl Iterator for SignalSetIter {
     type Item = Signal;
 
     fn next(&mut self) -> Option<Self::Item> {
         let n = self.0.trailing_zeros();
-        match n {
-            0 => Some(Signal::Interrupt),
-            1 => Some(Signal::Quit),
-            2 => Some(Signal::Terminate),
-            3 => Some(Signal::User1),
-            4 => Some(Signal::User2),
-            _ => None,
-        }
-        .map(|signal| {
-            // Remove the signal from the set.
-            self.0 &= !(1 << n);
-            signal
-        })
+        let signal = match n {
+            0 => Signal::Interrupt,
+            1 => Signal::Quit,
+            2 => Signal::Terminate,
+            3 => Signal::User1,
+            4 => Signal::User2,
+            5 => Signal::Hangup,
+            6 => Signal::Child,
+            n if (REALTIME_SHIFT..128).contains(&n) => Signal::Realtime((n - REALTIME_SHIFT) as u8),
+            _ => return None,
+        };
+        // Remove the signal from the set.
+        self.0 &= !(1 << n);
+        Some(signal)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -379,6 +1116,86 @@ pub enum Signal {
     ///
     /// Corresponds to POSIX signal `SIGUSR2`.
     User2,
+    /// Hangup signal.
+    ///
+    /// This signal is received when the controlling terminal is closed. Daemons,
+    /// which don't have a controlling terminal, often use this signal as a
+    /// request to reload their configuration.
+    ///
+    /// Corresponds to POSIX signal `SIGHUP`.
+    Hangup,
+    /// Child process signal.
+    ///
+    /// This signal is received when a child process terminated, stopped or
+    /// continued. See [`ChildWatcher`] for a type that also reaps the child
+    /// processes.
+    ///
+    /// Corresponds to POSIX signal `SIGCHLD`.
+    Child,
+    /// Real-time signal.
+    ///
+    /// Real-time signals don't have a predefined meaning and can be used for
+    /// application defined purposes. Unlike the other signals, multiple
+    /// instances of real-time signals are queued (and thus received) instead of
+    /// merged into a single signal.
+    ///
+    /// The value is the offset from `SIGRTMIN`, i.e. `Realtime(0)` is `SIGRTMIN`
+    /// and `Realtime(1)` is `SIGRTMIN+1`, etc. `SIGRTMIN` is determined at
+    /// runtime as the C library can reserve some real-time signals for internal
+    /// use (e.g. glibc reserves the first two). The maximum offset is thus
+    /// `SIGRTMAX - SIGRTMIN`, using an offset outside of this range will cause
+    /// [`Signals::new`] and [`send_signal`] to return an error.
+    ///
+    /// # Notes
+    ///
+    /// Real-time signals are currently only supported on Android and Linux.
+    ///
+    /// A [`SignalSet`] can only hold offsets smaller than 120, larger offsets
+    /// are stored as offset 119. This is beyond the number of real-time
+    /// signals supported by any platform, so [`Signals::new`] will still
+    /// return an error for such a set.
+    Realtime(u8),
+}
+
+impl Signal {
+    /// Exit the process because of this signal, such that the parent process
+    /// sees it was terminated by the signal (i.e. [`WIFSIGNALED`] is true).
+    ///
+    /// This is what a process should do after cleaning up when it receives
+    /// e.g. [`Signal::Interrupt`], so that shells can react correctly to
+    /// Ctrl+C. This restores the default signal action (`SIG_DFL`), unblocks
+    /// the signal and raises it in the current thread. This undoes the signal
+    /// mask or action set by [`Signals`], which shouldn't be used afterwards.
+    ///
+    /// If the default action of the signal doesn't terminate the process, e.g.
+    /// for [`Signal::Child`], the process exits with code `128` plus the signal
+    /// number, like shells do. If the signal isn't supported on this platform
+    /// the process exits with code `1`.
+    ///
+    /// Like [`process::exit`] no destructors are run.
+    ///
+    /// [`WIFSIGNALED`]: https://man7.org/linux/man-pages/man2/waitpid.2.html
+    /// [`process::exit`]: std::process::exit
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io;
+    ///
+    /// use mio_signals::{Signal, Signals};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut signals = Signals::new(Signal::Interrupt | Signal::Terminate)?;
+    ///
+    ///     let signal = signals.wait()?;
+    ///     // Clean up, then exit because of the signal.
+    ///     drop(signals);
+    ///     signal.exit_by()
+    /// }
+    /// ```
+    pub fn exit_by(self) -> ! {
+        sys::exit_by(self)
+    }
 }
 
 impl BitOr for Signal {
@@ -397,8 +1214,159 @@ impl BitOr<SignalSet> for Signal {
     }
 }
 
+/// Information about a received signal, returned by
+/// [`Signals::receive_info`].
+///
+/// # Notes
+///
+/// Only Android and Linux, or [`Signals::new_self_pipe`] on any platform,
+/// provide information other than the signal itself, otherwise all methods,
+/// except [`SignalInfo::signal`], return `None`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use mio_signals::{Signal, Signals};
+///
+/// fn main() -> io::Result<()> {
+///     let mut signals = Signals::new(Signal::Terminate.into())?;
+///
+///     // Normally you would register `signals` with `Poll` and wait for an
+///     // event, see the `Signals` documentation.
+///     while let Some(info) = signals.receive_info()? {
+///         match info.pid() {
+///             Some(pid) => println!("Got signal {:?} from process {}", info.signal(), pid),
+///             None => println!("Got signal {:?}", info.signal()),
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SignalInfo {
+    signal: Signal,
+    code: Option<SignalCode>,
+    pid: Option<u32>,
+    uid: Option<u32>,
+    value: Option<u64>,
+    child_status: Option<i32>,
+    child_user_time: Option<Duration>,
+    child_system_time: Option<Duration>,
+}
+
+impl SignalInfo {
+    /// Create a new `SignalInfo` with only the signal.
+    ///
+    /// This can be used to create a buffer for [`Signals::receive_batch`].
+    pub const fn new(signal: Signal) -> SignalInfo {
+        SignalInfo {
+            signal,
+            code: None,
+            pid: None,
+            uid: None,
+            value: None,
+            child_status: None,
+            child_user_time: None,
+            child_system_time: None,
+        }
+    }
+
+    /// The received signal.
+    pub const fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// Reason the signal was sent, see [`SignalCode`].
+    pub const fn code(&self) -> Option<SignalCode> {
+        self.code
+    }
+
+    /// Process id of the sending process.
+    ///
+    /// For [`Signal::Child`] this is the process id of the child process.
+    /// Signals send by the kernel have a process id of zero.
+    pub const fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Real user id of the sending process.
+    pub const fn uid(&self) -> Option<u32> {
+        self.uid
+    }
+
+    /// Value passed to [`sigqueue(3)`], only available for signals with
+    /// [`SignalCode::Queue`].
+    ///
+    /// If the sender used the `sival_int` field, rather than `sival_ptr`, the
+    /// value can be retrieved by casting this to `i32`.
+    ///
+    /// [`sigqueue(3)`]: https://man7.org/linux/man-pages/man3/sigqueue.3.html
+    pub const fn value(&self) -> Option<u64> {
+        self.value
+    }
+
+    /// Exit status, or signal number, of the child process, only available for
+    /// [`Signal::Child`].
+    ///
+    /// Whether this is an exit status or a signal number depends on the reason
+    /// the signal was sent, see the `CLD_*` constants in [`sigaction(2)`] and
+    /// [`SignalCode::Other`].
+    ///
+    /// [`sigaction(2)`]: https://man7.org/linux/man-pages/man2/sigaction.2.html
+    pub const fn child_status(&self) -> Option<i32> {
+        self.child_status
+    }
+
+    /// User CPU time consumed by the child process, only available for
+    /// [`Signal::Child`].
+    pub const fn child_user_time(&self) -> Option<Duration> {
+        self.child_user_time
+    }
+
+    /// System CPU time consumed by the child process, only available for
+    /// [`Signal::Child`].
+    pub const fn child_system_time(&self) -> Option<Duration> {
+        self.child_system_time
+    }
+}
+
+/// Reason a signal was sent, part of [`SignalInfo`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SignalCode {
+    /// Send by a process using [`kill(2)`], e.g. using [`send_signal`].
+    ///
+    /// Corresponds to `SI_USER`.
+    ///
+    /// [`kill(2)`]: https://man7.org/linux/man-pages/man2/kill.2.html
+    User,
+    /// Send by the kernel.
+    ///
+    /// Corresponds to `SI_KERNEL`.
+    Kernel,
+    /// Send by a process using [`sigqueue(3)`], see [`SignalInfo::value`].
+    ///
+    /// Corresponds to `SI_QUEUE`.
+    ///
+    /// [`sigqueue(3)`]: https://man7.org/linux/man-pages/man3/sigqueue.3.html
+    Queue,
+    /// Send to a specific thread using [`tgkill(2)`], e.g. using
+    /// `pthread_kill(3)` or `raise(3)`.
+    ///
+    /// Corresponds to `SI_TKILL`.
+    ///
+    /// [`tgkill(2)`]: https://man7.org/linux/man-pages/man2/tgkill.2.html
+    Tkill,
+    /// Any other code, e.g. the `CLD_*` codes for [`Signal::Child`].
+    Other(i32),
+}
+
 /// Send `signal` to the process with `pid`.
 ///
+/// This is a shorthand for [`send_signal_to`] using [`Target::Process`].
+/// Returns an error if `pid` is not a valid process id, see [`Pid::new`].
+///
 /// # Examples
 ///
 /// Send ourselves a signal.
@@ -450,5 +1418,31 @@ impl BitOr<SignalSet> for Signal {
 /// }
 /// ```
 pub fn send_signal(pid: u32, signal: Signal) -> io::Result<()> {
-    sys::send_signal(pid, signal)
+    Pid::new(pid).and_then(|pid| send_signal_to(Target::Process(pid), signal))
+}
+
+/// Send `signal` to `target`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::Command;
+///
+/// use mio_signals::{send_signal_to, Pid, Signal, Target};
+///
+/// fn main() -> io::Result<()> {
+///     let mut child = Command::new("sleep").arg("10").spawn()?;
+///
+///     // Ask the child process to stop.
+///     let pid = Pid::new(child.id())?;
+///     send_signal_to(Target::Process(pid), Signal::Terminate)?;
+///
+///     let status = child.wait()?;
+///     assert!(!status.success());
+///     Ok(())
+/// }
+/// ```
+pub fn send_signal_to(target: Target, signal: Signal) -> io::Result<()> {
+    sys::send_signal(target, signal)
 }
@@ -0,0 +1,251 @@
+//! Module with [`ProcessHandle`] and [`ProcessExit`].
+
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+use std::{fmt, io};
+
+use log::error;
+use mio::unix::SourceFd;
+use mio::{event, Interest, Registry, Token};
+
+use crate::{sys, Pid, Signal};
+
+/// Handle to a process, backed by a [`pidfd(2)`].
+///
+/// Unlike a process id a process handle always refers to the same process, even
+/// after the process terminated and its process id is reused by another
+/// process. This means that [`ProcessHandle::send_signal`] never sends a signal
+/// to the wrong process, which [`send_signal`] can do if the process was
+/// already reaped.
+///
+/// Requires Linux 5.3 or later, on older kernels [`ProcessHandle::from_pid`]
+/// returns an error of kind [`io::ErrorKind::Unsupported`].
+///
+/// [`pidfd(2)`]: https://man7.org/linux/man-pages/man2/pidfd_open.2.html
+/// [`send_signal`]: crate::send_signal
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::os::unix::process::ExitStatusExt;
+/// use std::process::Command;
+///
+/// use mio_signals::{ProcessHandle, Signal};
+///
+/// fn main() -> io::Result<()> {
+///     let mut child = Command::new("sleep").arg("10").spawn()?;
+///
+///     // Ask the child process to stop.
+///     let handle = ProcessHandle::from_child(&child)?;
+///     handle.send_signal(Signal::Terminate)?;
+///
+///     let status = child.wait()?;
+///     assert_eq!(status.signal(), Some(libc::SIGTERM));
+///     Ok(())
+/// }
+/// ```
+pub struct ProcessHandle {
+    /// Process file descriptor.
+    fd: RawFd,
+}
+
+impl ProcessHandle {
+    /// Create a handle to the process with `pid`.
+    ///
+    /// # Notes
+    ///
+    /// If the process already terminated, and was reaped, `pid` could refer to
+    /// another process. Use [`ProcessHandle::from_child`] if possible.
+    pub fn from_pid(pid: Pid) -> io::Result<ProcessHandle> {
+        sys::pidfd_open(pid.id()).map(|fd| ProcessHandle { fd })
+    }
+
+    /// Create a handle to the `child` process.
+    ///
+    /// # Notes
+    ///
+    /// This must be called before `child` is waited on (e.g. using
+    /// [`Child::wait`]), otherwise the process is reaped and its process id
+    /// could refer to another process.
+    pub fn from_child(child: &Child) -> io::Result<ProcessHandle> {
+        Pid::new(child.id()).and_then(ProcessHandle::from_pid)
+    }
+
+    /// Send `signal` to the process.
+    ///
+    /// Requires Linux 5.1 or later, on older kernels this returns an error of
+    /// kind [`io::ErrorKind::Unsupported`].
+    pub fn send_signal(&self, signal: Signal) -> io::Result<()> {
+        sys::pidfd_send_signal(self.fd, signal)
+    }
+}
+
+impl AsRawFd for ProcessHandle {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl fmt::Debug for ProcessHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessHandle")
+            .field("fd", &self.fd)
+            .finish()
+    }
+}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        if unsafe { libc::close(self.fd) } == -1 {
+            let err = io::Error::last_os_error();
+            error!("error closing ProcessHandle: {}", err);
+        }
+    }
+}
+
+/// Notification of a process exiting.
+///
+/// `ProcessExit` can be registered with [`mio::Poll`], becoming readable once
+/// the process exits. Unlike [`ChildWatcher`] this works for any process, not
+/// just child processes of this process.
+///
+/// If the process is a child of this process its exit status can be retrieved
+/// using [`ProcessExit::exit_status`], this also reaps the child process.
+///
+/// Requires Linux 5.3 or later, see [`ProcessHandle`].
+///
+/// [`ChildWatcher`]: crate::ChildWatcher
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process::Command;
+///
+/// use mio::{Poll, Events, Interest, Token};
+/// use mio_signals::ProcessExit;
+///
+/// const EXIT: Token = Token(10);
+///
+/// fn main() -> io::Result<()> {
+///     let mut poll = Poll::new()?;
+///     let mut events = Events::with_capacity(8);
+///
+///     let child = Command::new("true").spawn()?;
+///     let mut exit = ProcessExit::from_child(&child)?;
+///     poll.registry().register(&mut exit, EXIT, Interest::READABLE)?;
+///
+///     loop {
+///         poll.poll(&mut events, None)?;
+///
+///         for event in events.iter() {
+///             match event.token() {
+///                 EXIT if exit.has_exited()? => {
+///                     let status = exit.exit_status()?;
+///                     println!("child process exited: {:?}", status);
+///                     return Ok(());
+///                 }
+///                 _ => println!("Got unexpected event: {:?}", event),
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProcessExit {
+    handle: ProcessHandle,
+    /// Exit status of the child process, once reaped.
+    status: Option<ExitStatus>,
+}
+
+impl ProcessExit {
+    /// Create a notifier for the process with `pid` exiting.
+    ///
+    /// See [`ProcessHandle::from_pid`].
+    pub fn from_pid(pid: Pid) -> io::Result<ProcessExit> {
+        ProcessHandle::from_pid(pid).map(ProcessExit::from)
+    }
+
+    /// Create a notifier for the `child` process exiting.
+    ///
+    /// See [`ProcessHandle::from_child`].
+    pub fn from_child(child: &Child) -> io::Result<ProcessExit> {
+        ProcessHandle::from_child(child).map(ProcessExit::from)
+    }
+
+    /// Returns the handle to the process.
+    pub const fn handle(&self) -> &ProcessHandle {
+        &self.handle
+    }
+
+    /// Returns `true` if the process has exited.
+    ///
+    /// This doesn't block.
+    pub fn has_exited(&self) -> io::Result<bool> {
+        if self.status.is_some() {
+            Ok(true)
+        } else {
+            sys::poll_readable(&[self.handle.fd], Some(Duration::ZERO))
+        }
+    }
+
+    /// Returns the exit status of the process, if it exited and is a child of
+    /// this process.
+    ///
+    /// This reaps the child process, meaning that calling [`Child::wait`] or
+    /// [`Child::try_wait`] will return an error afterwards. Once reaped the
+    /// exit status is returned by all following calls.
+    ///
+    /// If the process hasn't exited yet, or isn't a child of this process, this
+    /// returns `Ok(None)`. Use [`ProcessExit::has_exited`] to determine if the
+    /// process exited.
+    ///
+    /// Requires Linux 5.4 or later, on older kernels this returns an error of
+    /// kind [`io::ErrorKind::Unsupported`].
+    pub fn exit_status(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_none() {
+            self.status = match sys::pidfd_wait(self.handle.fd) {
+                Ok(status) => status,
+                // Not our child process.
+                Err(ref err) if err.raw_os_error() == Some(libc::ECHILD) => None,
+                Err(err) => return Err(err),
+            };
+        }
+        Ok(self.status)
+    }
+}
+
+impl From<ProcessHandle> for ProcessExit {
+    fn from(handle: ProcessHandle) -> ProcessExit {
+        ProcessExit {
+            handle,
+            status: None,
+        }
+    }
+}
+
+impl event::Source for ProcessExit {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.handle.fd).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.handle.fd).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.handle.fd).deregister(registry)
+    }
+}
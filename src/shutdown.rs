@@ -0,0 +1,286 @@
+//! Module with [`Shutdown`], [`ShutdownHandle`] and [`ShutdownState`].
+
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use mio::{event, Interest, Registry, Token};
+
+use crate::Signals;
+
+/// Graceful shutdown coordinator.
+///
+/// `Shutdown` is build on top of [`Signals`] and implements the common rules
+/// for shutting down a process:
+///
+///  * the first received signal starts a graceful shutdown, changing the state
+///    to [`ShutdownState::Draining`],
+///  * a second received signal forces the shutdown, changing the state to
+///    [`ShutdownState::Forced`],
+///  * if the graceful shutdown takes longer than the grace period the shutdown
+///    is forced as well.
+///
+/// Other threads can check or wait on the state using a [`ShutdownHandle`],
+/// see [`Shutdown::handle`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::process;
+/// use std::time::Duration;
+///
+/// use mio::{Events, Interest, Poll, Token};
+/// use mio_signals::{send_signal, Shutdown, ShutdownState, Signal, Signals};
+///
+/// const SHUTDOWN: Token = Token(10);
+///
+/// fn main() -> io::Result<()> {
+///     let mut poll = Poll::new()?;
+///     let mut events = Events::with_capacity(8);
+///
+///     let signals = Signals::new(Signal::Interrupt | Signal::Terminate)?;
+///     let mut shutdown = Shutdown::new(signals, Duration::from_secs(10));
+///     poll.registry().register(&mut shutdown, SHUTDOWN, Interest::READABLE)?;
+///
+///     // Handle to the state for other threads.
+///     let handle = shutdown.handle();
+///
+///     // Ask ourselves to stop.
+///     send_signal(process::id(), Signal::Terminate)?;
+///
+///     loop {
+///         // Wake up in time to force the shutdown once the grace period is
+///         // over.
+///         poll.poll(&mut events, shutdown.timeout())?;
+///
+///         match shutdown.receive()? {
+///             ShutdownState::Running => {},
+///             ShutdownState::Draining => {
+///                 println!("Shutting down gracefully");
+///                 assert_eq!(handle.state(), ShutdownState::Draining);
+///                 // In a real program we would finish our work first.
+///                 return Ok(());
+///             },
+///             ShutdownState::Forced => {
+///                 println!("Forced shutdown");
+///                 return Ok(());
+///             },
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Shutdown {
+    signals: Signals,
+    shared: Arc<Shared>,
+}
+
+/// State of the shutdown, see [`Shutdown`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ShutdownState {
+    /// No signal was received yet, the process should keep running.
+    Running,
+    /// A signal was received, the process should shut down gracefully.
+    Draining,
+    /// A second signal was received or the grace period is over, the process
+    /// should exit as soon as possible.
+    Forced,
+}
+
+/// Handle to the state of a [`Shutdown`], see [`Shutdown::handle`].
+///
+/// The handle can be cloned and shared between threads.
+#[derive(Clone, Debug)]
+pub struct ShutdownHandle {
+    shared: Arc<Shared>,
+}
+
+/// State shared between `Shutdown` and `ShutdownHandle`s.
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when the state changes.
+    changed: Condvar,
+    grace: Duration,
+}
+
+#[derive(Debug)]
+struct State {
+    state: ShutdownState,
+    /// Time at which the shutdown is forced, set once draining. Remains `None`
+    /// if the grace period is too long to compute a deadline.
+    deadline: Option<Instant>,
+}
+
+impl Shutdown {
+    /// Create a new shutdown coordinator, using all signals received by
+    /// `signals` and allowing `grace` time for the graceful shutdown.
+    pub fn new(signals: Signals, grace: Duration) -> Shutdown {
+        let state = State {
+            state: ShutdownState::Running,
+            deadline: None,
+        };
+        Shutdown {
+            signals,
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                changed: Condvar::new(),
+                grace,
+            }),
+        }
+    }
+
+    /// Receive all signals, returning the current state.
+    ///
+    /// This also forces the shutdown if the grace period is over.
+    pub fn receive(&mut self) -> io::Result<ShutdownState> {
+        while self.signals.receive()?.is_some() {
+            self.shared.escalate();
+        }
+        Ok(self.state())
+    }
+
+    /// Returns the current state.
+    ///
+    /// This doesn't receive any signals, see [`Shutdown::receive`].
+    pub fn state(&self) -> ShutdownState {
+        self.shared.state()
+    }
+
+    /// Returns the time left in the grace period, if draining. Returns `None`
+    /// if the grace period is too long to compute a deadline.
+    ///
+    /// This can be used as timeout in [`Poll::poll`] to force the shutdown in
+    /// time, see the example in the type documentation.
+    ///
+    /// [`Poll::poll`]: mio::Poll::poll
+    pub fn timeout(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.shared
+            .lock()
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(now))
+    }
+
+    /// Returns a handle to the state.
+    pub fn handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl event::Source for Shutdown {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.signals.reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.signals.deregister(registry)
+    }
+}
+
+impl ShutdownHandle {
+    /// Returns the current state.
+    pub fn state(&self) -> ShutdownState {
+        self.shared.state()
+    }
+
+    /// Wait until the shutdown started, returning the current state (either
+    /// draining or forced).
+    pub fn wait(&self) -> ShutdownState {
+        let mut state = self.shared.lock();
+        while state.state == ShutdownState::Running {
+            state = self
+                .shared
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        self.shared.update(&mut state)
+    }
+
+    /// Wait until the shutdown is forced.
+    pub fn wait_forced(&self) {
+        let mut state = self.shared.lock();
+        loop {
+            // The deadline is `None` if we're running or if the grace period is
+            // too long to have a deadline, in which case we wait for a signal.
+            let deadline = match self.shared.update(&mut state) {
+                ShutdownState::Forced => return,
+                ShutdownState::Draining | ShutdownState::Running => state.deadline,
+            };
+            state = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    self.shared
+                        .changed
+                        .wait_timeout(state, timeout)
+                        .map(|(state, _)| state)
+                        .unwrap_or_else(|err| err.into_inner().0)
+                }
+                None => self
+                    .shared
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the current state, forcing the shutdown if the grace period is
+    /// over.
+    fn state(&self) -> ShutdownState {
+        self.update(&mut self.lock())
+    }
+
+    /// Escalate the state after receiving a signal.
+    fn escalate(&self) {
+        let mut state = self.lock();
+        match state.state {
+            ShutdownState::Running => {
+                state.state = ShutdownState::Draining;
+                state.deadline = Instant::now().checked_add(self.grace);
+            }
+            ShutdownState::Draining | ShutdownState::Forced => {
+                state.state = ShutdownState::Forced;
+                state.deadline = None;
+            }
+        }
+        self.changed.notify_all();
+    }
+
+    /// Force the shutdown if the grace period is over, returning the current
+    /// state.
+    fn update(&self, state: &mut State) -> ShutdownState {
+        if let Some(deadline) = state.deadline {
+            if Instant::now() >= deadline {
+                state.state = ShutdownState::Forced;
+                state.deadline = None;
+                self.changed.notify_all();
+            }
+        }
+        state.state
+    }
+}
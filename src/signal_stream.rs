@@ -0,0 +1,98 @@
+//! Module with [`SignalStream`].
+
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use async_io::Async;
+use futures_core::Stream;
+
+use crate::{Signal, SignalSet, Signals};
+
+/// [`Stream`] of process signals, usable with any executor.
+///
+/// This wraps [`Signals`], using the same file descriptor and receive path,
+/// but is driven by the [async-io] reactor instead of [`mio::Poll`]. Because
+/// it only relies on the reactor, and not on an executor, it can be used with
+/// any executor, e.g. in `select!` together with other futures.
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation. This is especially important as the
+/// reactor can be polled from another thread, which must also have the
+/// signals blocked.
+///
+/// This type is only available if the `futures` feature is enabled.
+///
+/// [async-io]: https://docs.rs/async-io
+///
+/// # Examples
+///
+/// ```
+/// use std::future::poll_fn;
+/// use std::pin::Pin;
+/// use std::{io, process};
+///
+/// use futures_core::Stream;
+/// use mio_signals::{send_signal, Signal, SignalStream};
+///
+/// fn main() -> io::Result<()> {
+///     let mut signals = SignalStream::new(Signal::Interrupt.into())?;
+///     # // Don't want to wait for ever.
+///     # send_signal(process::id(), Signal::Interrupt)?;
+///
+///     async_io::block_on(async {
+///         let next = poll_fn(|ctx| Pin::new(&mut signals).poll_next(ctx)).await;
+///         assert_eq!(next.transpose()?, Some(Signal::Interrupt));
+///         Ok(())
+///     })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SignalStream {
+    inner: Async<Signals>,
+}
+
+impl SignalStream {
+    /// Create a new signal stream.
+    ///
+    /// See [`Signals::new`].
+    pub fn new(signals: SignalSet) -> io::Result<SignalStream> {
+        Signals::new(signals).and_then(SignalStream::from_signals)
+    }
+
+    /// Create a new signal stream from `signals`.
+    pub fn from_signals(signals: Signals) -> io::Result<SignalStream> {
+        Async::new(signals).map(|inner| SignalStream { inner })
+    }
+
+    /// Returns a reference to the underlying [`Signals`].
+    pub fn get_ref(&self) -> &Signals {
+        self.inner.get_ref()
+    }
+
+    /// Returns the underlying [`Signals`], deregistering it from the reactor.
+    pub fn into_inner(self) -> io::Result<Signals> {
+        self.inner.into_inner()
+    }
+}
+
+impl Stream for SignalStream {
+    type Item = io::Result<Signal>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            // This is safe because `receive` doesn't close or replace the file
+            // descriptor.
+            match unsafe { this.inner.get_mut() }.receive() {
+                Ok(Some(signal)) => return Poll::Ready(Some(Ok(signal))),
+                Ok(None) => {}
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+
+            if let Err(err) = ready!(this.inner.poll_readable(ctx)) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
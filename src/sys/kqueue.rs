@@ -1,14 +1,21 @@
+use std::cmp::min;
 use std::mem::MaybeUninit;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
 use std::{io, ptr};
 
 use log::error;
 use mio::unix::SourceFd;
 use mio::{event, Interest, Registry, Token};
 
-use crate::{Signal, SignalSet};
+use crate::{Signal, SignalInfo, SignalSet, UnblockedThread, REALTIME_SHIFT};
 
-use super::{from_raw_signal, raw_signal};
+use super::registry::SignalRegistry;
+use super::{from_raw_signal, poll_readable, raw_signal};
+
+/// Registry of the signals we ignored, keeping track of the previous signal
+/// action (if changed).
+static REGISTRY: SignalRegistry<Option<libc::sigaction>> = SignalRegistry::new();
 
 /// Signaler backed that uses `kqueue(2)`'s `EVFILT_SIGNAL`.
 ///
@@ -21,23 +28,50 @@ use super::{from_raw_signal, raw_signal};
 ///
 /// We set the signal handler to ignore the signal (not blocking them like in
 /// the signalfd implementation) to ensure the signal doesn't grow endlessly.
+///
+/// Multiple `Signals` can use the same signal, so we only restore the signal
+/// action once the last `Signals` using it is dropped (see `REGISTRY`).
 #[derive(Debug)]
 pub struct Signals {
     /// `kqueue(2)` file descriptor.
     kq: RawFd,
     /// All signals this is listening for, used in resetting the signal handlers.
     signals: SignalSet,
+    /// Whether or not to restore the signal actions when dropped.
+    restore: bool,
 }
 
 impl Signals {
     pub fn new(signals: SignalSet) -> io::Result<Signals> {
         new_kqueue()
-            .map(|kq| Signals { kq, signals })
-            .and_then(|kq| register_signals(kq.kq, signals).map(|()| kq))
-            .and_then(|kq| ignore_signals(signals).map(|()| kq))
+            .and_then(|kq| match register_signals(kq, signals) {
+                Ok(()) => Ok(kq),
+                Err(err) => {
+                    // Don't leak the file descriptor. Can't do anything about
+                    // errors here.
+                    let _ = unsafe { libc::close(kq) };
+                    Err(err)
+                }
+            })
+            .and_then(|kq| match ignore_signals(signals) {
+                Ok(()) => Ok(Signals {
+                    kq,
+                    signals,
+                    restore: true,
+                }),
+                Err(err) => {
+                    let _ = unsafe { libc::close(kq) };
+                    Err(err)
+                }
+            })
     }
 
-    pub fn receive(&mut self) -> io::Result<Option<Signal>> {
+    pub fn new_fan_out(signals: SignalSet) -> io::Result<Signals> {
+        // Every `kqueue` receives its own notification of each signal.
+        Signals::new(signals)
+    }
+
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
         let mut kevent: MaybeUninit<libc::kevent> = MaybeUninit::uninit();
         // No blocking.
         let timeout = libc::timespec {
@@ -59,11 +93,65 @@ impl Signals {
                 debug_assert_eq!(filter, libc::EVFILT_SIGNAL);
                 // This should never return `None` as we control the signals we
                 // register for, which is always defined in terms of `Signal`.
-                Ok(from_raw_signal(kevent.ident as libc::c_int))
+                // `EVFILT_SIGNAL` doesn't provide any information about the
+                // signal other than the number of times it was delivered.
+                Ok(from_raw_signal(kevent.ident as libc::c_int).map(SignalInfo::new))
             }
             _ => unreachable!("unexpected number of events"),
         }
     }
+
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        let mut kevents: [MaybeUninit<libc::kevent>; BATCH_SIZE] =
+            [MaybeUninit::uninit(); BATCH_SIZE];
+        let max = min(infos.len(), BATCH_SIZE);
+        // No blocking.
+        let timeout = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        let n_events = unsafe {
+            libc::kevent(
+                self.kq,
+                ptr::null(),
+                0,
+                kevents.as_mut_ptr().cast(),
+                max as libc::c_int,
+                &timeout,
+            )
+        };
+        if n_events == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut n = 0;
+        for kevent in &kevents[..n_events as usize] {
+            // This is safe because `kevent` ensures that the events are
+            // initialised.
+            let kevent = unsafe { kevent.assume_init() };
+            // See `receive_info`.
+            if let Some(signal) = from_raw_signal(kevent.ident as libc::c_int) {
+                infos[n] = SignalInfo::new(signal);
+                n += 1;
+            }
+        }
+        Ok(n)
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        poll_readable(&[self.kq], timeout)
+    }
+
+    pub fn check_thread_masks(&self) -> io::Result<Vec<UnblockedThread>> {
+        // We ignore the signals, rather than blocking them, so the signal mask
+        // of the threads doesn't matter.
+        Ok(Vec::new())
+    }
+
+    pub fn set_restore(&mut self, restore: bool) {
+        self.restore = restore;
+    }
 }
 
 fn new_kqueue() -> io::Result<RawFd> {
@@ -78,8 +166,10 @@ fn new_kqueue() -> io::Result<RawFd> {
 fn register_signals(kq: RawFd, signals: SignalSet) -> io::Result<()> {
     // For each signal create an kevent to indicate we want events for
     // those signals.
-    let mut changes: [MaybeUninit<libc::kevent>; SignalSet::all().len()] =
-        [MaybeUninit::uninit(); SignalSet::all().len()];
+    // Real-time signals aren't supported, so `signals` contains at most the
+    // named signals, all of which are stored before `REALTIME_SHIFT`.
+    let mut changes: [MaybeUninit<libc::kevent>; REALTIME_SHIFT as usize] =
+        [MaybeUninit::uninit(); REALTIME_SHIFT as usize];
     let mut n_changes = 0;
     for signal in signals {
         changes[n_changes] = MaybeUninit::new(libc::kevent {
@@ -127,28 +217,50 @@ fn register_signals(kq: RawFd, signals: SignalSet) -> io::Result<()> {
 
 /// Ignore all signals in the `signals` set.
 fn ignore_signals(signals: SignalSet) -> io::Result<()> {
-    sigaction(signals, libc::SIG_IGN)
+    REGISTRY.add(signals, ignore_signal, restore_signal)
 }
 
-/// Inverse of `ignore_signals`, resetting all signal handlers to the default.
+/// Inverse of `ignore_signals`, restoring the signal actions of all `signals`
+/// that are no longer used.
 fn unignore_signals(signals: SignalSet) -> io::Result<()> {
-    sigaction(signals, libc::SIG_DFL)
+    REGISTRY.remove(signals, restore_signal)
 }
 
-/// Call `sigaction` for each signal in `signals`, using `action` as signal
-/// handler.
-fn sigaction(signals: SignalSet, action: libc::sighandler_t) -> io::Result<()> {
+/// Ignore `signal`, returning the previous signal action.
+fn ignore_signal(signal: Signal) -> io::Result<Option<libc::sigaction>> {
+    // Ignoring `SIGCHLD` causes child processes to be reaped automatically,
+    // which means they can't be waited on anymore. The default action for
+    // `SIGCHLD` is already to ignore it, so we leave it as is.
+    if signal == Signal::Child {
+        return Ok(None);
+    }
+
     let action = libc::sigaction {
-        sa_sigaction: action,
+        sa_sigaction: libc::SIG_IGN,
         sa_mask: empty_sigset()?,
         sa_flags: 0,
     };
-    for signal in signals {
-        if unsafe { libc::sigaction(raw_signal(signal), &action, ptr::null_mut()) } == -1 {
-            return Err(io::Error::last_os_error());
+    let mut old_action: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+    if unsafe { libc::sigaction(raw_signal(signal), &action, old_action.as_mut_ptr()) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        // This is safe because `sigaction` initialised the old action.
+        Ok(Some(unsafe { old_action.assume_init() }))
+    }
+}
+
+/// Inverse of `ignore_signal`, restoring the signal action to `old_action`.
+fn restore_signal(signal: Signal, old_action: Option<libc::sigaction>) -> io::Result<()> {
+    match old_action {
+        Some(old_action) => {
+            if unsafe { libc::sigaction(raw_signal(signal), &old_action, ptr::null_mut()) } == -1 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
         }
+        None => Ok(()),
     }
-    Ok(())
 }
 
 /// Create an empty `sigset_t`.
@@ -162,6 +274,15 @@ fn empty_sigset() -> io::Result<libc::sigset_t> {
     }
 }
 
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.kq
+    }
+}
+
+/// Maximum number of events to receive at once.
+const BATCH_SIZE: usize = 32;
+
 impl event::Source for Signals {
     fn register(
         &mut self,
@@ -188,8 +309,14 @@ impl event::Source for Signals {
 
 impl Drop for Signals {
     fn drop(&mut self) {
-        // Reverse the ignoring of signals.
-        if let Err(err) = unignore_signals(self.signals) {
+        // Reverse the ignoring of signals. Even if we don't restore them we
+        // still need to remove our use of the signals.
+        let res = if self.restore {
+            unignore_signals(self.signals)
+        } else {
+            REGISTRY.remove(self.signals, |_, _| Ok(()))
+        };
+        if let Err(err) = res {
             error!("error resetting signal action: {}", err);
         }
 
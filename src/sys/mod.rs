@@ -1,6 +1,18 @@
 //! Platform dependent implementation of Signals.
 
-use crate::Signal;
+use std::mem::{self, MaybeUninit};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{self, ExitStatus};
+use std::time::Duration;
+use std::{io, ptr};
+
+use mio::{event, Interest, Registry, Token};
+
+use crate::{
+    Backend, Blocking, Signal, SignalCode, SignalInfo, SignalSet, SignalsBuilder, Target,
+    UnblockedThread,
+};
 
 #[cfg(any(
     target_os = "dragonfly",
@@ -20,26 +32,471 @@ mod kqueue;
     target_os = "netbsd",
     target_os = "openbsd"
 ))]
-pub use self::kqueue::Signals;
+use self::kqueue::Signals as NativeSignals;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod signalfd;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
-pub use self::signalfd::Signals;
+use self::signalfd::Signals as NativeSignals;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod pidfd;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::pidfd::{pidfd_open, pidfd_send_signal, pidfd_wait};
+
+mod registry;
+mod self_pipe;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod wait_thread;
+
+/// Implementation of `Signals`, using either the platform specific
+/// implementation (`signalfd` or `kqueue`), the self-pipe or a thread.
+#[derive(Debug)]
+pub enum Signals {
+    Native(NativeSignals),
+    SelfPipe(self_pipe::Signals),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Thread(wait_thread::Signals),
+}
+
+impl Signals {
+    pub fn new_fan_out(signals: SignalSet) -> io::Result<Signals> {
+        if discards_ignored(Backend::Native) {
+            check_ignored_signals(signals)?;
+        }
+        NativeSignals::new_fan_out(signals).map(Signals::Native)
+    }
+
+    pub fn build(builder: &SignalsBuilder) -> io::Result<Signals> {
+        let signals = builder.signals;
+        if discards_ignored(builder.backend) {
+            if builder.reset_ignored {
+                reset_ignored_signals(signals)?;
+            } else {
+                check_ignored_signals(signals)?;
+            }
+        }
+
+        let mut sys = match builder.backend {
+            Backend::Native => NativeSignals::new(signals).map(Signals::Native),
+            Backend::SelfPipe => self_pipe::Signals::new(signals).map(Signals::SelfPipe),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Backend::Thread => wait_thread::Signals::new(signals).map(Signals::Thread),
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Backend::Thread => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the thread backend is only supported on Android and Linux",
+            )),
+        }?;
+
+        // The signal handler of the self-pipe is process-wide, the signal mask
+        // doesn't matter.
+        if builder.blocking == Blocking::Process && !matches!(sys, Signals::SelfPipe(_)) {
+            // NOTE: on error `sys` is dropped, restoring the signal mask of
+            // the current thread.
+            block_in_all_threads(signals)?;
+        }
+
+        if !builder.restore_on_drop {
+            match &mut sys {
+                Signals::Native(signals) => signals.set_restore(false),
+                Signals::SelfPipe(signals) => signals.set_restore(false),
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                Signals::Thread(signals) => signals.set_restore(false),
+            }
+        }
+        Ok(sys)
+    }
+
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
+        match self {
+            Signals::Native(signals) => signals.receive_info(),
+            Signals::SelfPipe(signals) => signals.receive_info(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals.receive_info(),
+        }
+    }
+
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        match self {
+            Signals::Native(signals) => signals.receive_batch(infos),
+            Signals::SelfPipe(signals) => signals.receive_batch(infos),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals.receive_batch(infos),
+        }
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        match self {
+            Signals::Native(signals) => signals.wait(timeout),
+            Signals::SelfPipe(signals) => signals.wait(timeout),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals.wait(timeout),
+        }
+    }
+
+    pub fn check_thread_masks(&self) -> io::Result<Vec<UnblockedThread>> {
+        match self {
+            Signals::Native(signals) => signals.check_thread_masks(),
+            // The signal handler is process-wide, so the signal mask of the
+            // threads doesn't matter.
+            Signals::SelfPipe(_) => Ok(Vec::new()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals.check_thread_masks(),
+        }
+    }
+
+    /// Returns the `event::Source` to register.
+    fn source(&mut self) -> &mut dyn event::Source {
+        match self {
+            Signals::Native(signals) => signals,
+            Signals::SelfPipe(signals) => signals,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals,
+        }
+    }
+}
+
+impl event::Source for Signals {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.source().register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        self.source().reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        self.source().deregister(registry)
+    }
+}
+
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Signals::Native(signals) => signals.as_raw_fd(),
+            Signals::SelfPipe(signals) => signals.as_raw_fd(),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Signals::Thread(signals) => signals.as_raw_fd(),
+        }
+    }
+}
 
 #[cfg(unix)]
-pub fn send_signal(pid: u32, signal: Signal) -> std::io::Result<()> {
-    if unsafe { libc::kill(pid as libc::pid_t, raw_signal(signal)) } != 0 {
-        Err(std::io::Error::last_os_error())
+pub fn send_signal(target: Target, signal: Signal) -> io::Result<()> {
+    check_signal(signal)?;
+    // NOTE: `Pid` and `Pgid` ensure the ids fit in `pid_t`.
+    let pid = match target {
+        Target::Process(pid) => pid.id() as libc::pid_t,
+        Target::ProcessGroup(pgid) => -(pgid.id() as libc::pid_t),
+        Target::OwnProcessGroup => 0,
+        Target::AllPermitted => -1,
+    };
+    if unsafe { libc::kill(pid, raw_signal(signal)) } != 0 {
+        Err(io::Error::last_os_error())
     } else {
         Ok(())
     }
 }
 
+/// Returns `true` if any of the `fds` is readable, waiting at most `timeout`
+/// (`None` means waiting forever). Negative file descriptors are ignored.
+#[cfg(unix)]
+pub fn poll_readable(fds: &[RawFd], timeout: Option<Duration>) -> io::Result<bool> {
+    let mut pollfds: Vec<libc::pollfd> = fds
+        .iter()
+        .map(|fd| libc::pollfd {
+            fd: *fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    let timeout = timeout.map_or(-1, |timeout| {
+        // Round up to ensure we don't return too early.
+        let millis = (timeout.as_nanos() + 999_999) / 1_000_000;
+        millis.min(libc::c_int::MAX as u128) as libc::c_int
+    });
+    let n = unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, timeout) };
+    match n {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
+
+/// Wait for the child process `pid`, or any child process if `pid` is `-1`,
+/// without blocking. This also reports stopped and continued child processes.
+#[cfg(unix)]
+pub fn wait_child(pid: libc::pid_t) -> io::Result<Option<(u32, ExitStatus)>> {
+    let mut status = 0;
+    loop {
+        let options = libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED;
+        match unsafe { libc::waitpid(pid, &mut status, options) } {
+            -1 => match io::Error::last_os_error() {
+                ref err if err.kind() == io::ErrorKind::Interrupted => continue,
+                // No (more) child processes to wait for.
+                ref err if err.raw_os_error() == Some(libc::ECHILD) => return Ok(None),
+                err => return Err(err),
+            },
+            // Child processes exist, but none changed state.
+            0 => return Ok(None),
+            pid => return Ok(Some((pid as u32, ExitStatus::from_raw(status)))),
+        }
+    }
+}
+
+/// Exit the process by raising `signal` with its default action, falling back
+/// to exiting with code `128 + signal`.
+pub fn exit_by(signal: Signal) -> ! {
+    if check_signal(signal).is_err() {
+        process::exit(1);
+    }
+
+    let signal = raw_signal(signal);
+    // Restore the default action, e.g. the kqueue implementation and the
+    // self-pipe change it.
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = libc::SIG_DFL;
+    let _ = unsafe { libc::sigemptyset(&mut action.sa_mask) };
+    let _ = set_signal_action(signal, &action);
+
+    // Unblock the signal, e.g. the signalfd implementation blocks it.
+    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    unsafe {
+        let _ = libc::sigemptyset(set.as_mut_ptr());
+        let _ = libc::sigaddset(set.as_mut_ptr(), signal);
+        let _ = libc::pthread_sigmask(libc::SIG_UNBLOCK, set.as_ptr(), ptr::null_mut());
+    }
+
+    // The signal is delivered to the current thread before `raise` returns, so
+    // if we get past this the default action doesn't terminate the process,
+    // e.g. for `SIGCHLD`.
+    let _ = unsafe { libc::raise(signal) };
+    process::exit(128 + signal)
+}
+
+/// Make the current process a child subreaper.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_child_subreaper() -> io::Result<()> {
+    if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_child_subreaper() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "child subreapers are only supported on Android and Linux",
+    ))
+}
+
+/// Unblock all signals in the current thread.
+///
+/// This only calls async-signal-safe functions, so it can be called after
+/// `fork(2)`.
+pub fn unblock_all_signals() -> io::Result<()> {
+    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    if unsafe { libc::sigemptyset(set.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    match unsafe { libc::pthread_sigmask(libc::SIG_SETMASK, set.as_ptr(), ptr::null_mut()) } {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+
 // TODO: add Windows implementation.
 
+/// Check if all signals in `signals` are supported on this platform.
+pub fn check_signals(signals: SignalSet) -> io::Result<()> {
+    signals.into_iter().try_for_each(check_signal)
+}
+
+/// Check if `signal` is supported on this platform.
+fn check_signal(signal: Signal) -> io::Result<()> {
+    match (signal, realtime_range()) {
+        (Signal::Realtime(n), Some((min, max))) if min + libc::c_int::from(n) > max => {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "real-time signal offset out of range",
+            ))
+        }
+        (Signal::Realtime(_), None) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "real-time signals are not supported on this platform",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the range of real-time signals, `SIGRTMIN` and `SIGRTMAX`
+/// (inclusive), if supported.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn realtime_range() -> Option<(libc::c_int, libc::c_int)> {
+    Some((libc::SIGRTMIN(), libc::SIGRTMAX()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn realtime_range() -> Option<(libc::c_int, libc::c_int)> {
+    None
+}
+
+/// Signal handler installed with `SA_SIGINFO`.
+type SignalHandler = extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void);
+
+/// Install `handler` for the raw signal `signal`, returning the previous signal
+/// action.
+fn set_signal_handler(signal: libc::c_int, handler: SignalHandler) -> io::Result<libc::sigaction> {
+    // This is safe because all zeroes is a valid `sigaction`.
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = handler as libc::sighandler_t;
+    action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+    if unsafe { libc::sigemptyset(&mut action.sa_mask) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut old_action: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+    if unsafe { libc::sigaction(signal, &action, old_action.as_mut_ptr()) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        // This is safe because `sigaction` initialised the old action.
+        Ok(unsafe { old_action.assume_init() })
+    }
+}
+
+/// Returns the signal action of the raw signal `signal`.
+fn get_signal_action(signal: libc::c_int) -> io::Result<libc::sigaction> {
+    let mut action: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+    if unsafe { libc::sigaction(signal, ptr::null(), action.as_mut_ptr()) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        // This is safe because `sigaction` initialised the action.
+        Ok(unsafe { action.assume_init() })
+    }
+}
+
+/// Returns true if ignored signals (`SIG_IGN`) can't be received by `backend`.
+///
+/// The kernel discards ignored signals before they're delivered, so they never
+/// reach the signalfd or `sigwaitinfo(2)`. kqueue records the signals even if
+/// they're ignored (it ignores them itself) and the self-pipe installs its own
+/// signal handler.
+fn discards_ignored(backend: Backend) -> bool {
+    match backend {
+        Backend::Native => cfg!(any(target_os = "linux", target_os = "android")),
+        Backend::SelfPipe => false,
+        Backend::Thread => true,
+    }
+}
+
+/// Returns an error if any of `signals` is ignored (`SIG_IGN`), see
+/// `discards_ignored`.
+fn check_ignored_signals(signals: SignalSet) -> io::Result<()> {
+    for signal in signals {
+        if get_signal_action(raw_signal(signal))?.sa_sigaction == libc::SIG_IGN {
+            let msg = format!(
+                "signal {:?} is ignored (SIG_IGN), possibly inherited from the parent \
+                 process (e.g. nohup), and would never be received; \
+                 use `SignalsBuilder::reset_ignored` to reset it",
+                signal
+            );
+            return Err(io::Error::new(io::ErrorKind::Other, msg));
+        }
+    }
+    Ok(())
+}
+
+/// Reset the signal action of all `signals` that are ignored (`SIG_IGN`) to
+/// the default action (`SIG_DFL`).
+fn reset_ignored_signals(signals: SignalSet) -> io::Result<()> {
+    for signal in signals {
+        let signal = raw_signal(signal);
+        let mut action = get_signal_action(signal)?;
+        if action.sa_sigaction == libc::SIG_IGN {
+            action.sa_sigaction = libc::SIG_DFL;
+            set_signal_action(signal, &action)?;
+        }
+    }
+    Ok(())
+}
+
+/// Block `signals` in all threads of the process.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn block_in_all_threads(signals: SignalSet) -> io::Result<()> {
+    signalfd::block_in_all_threads(signals)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn block_in_all_threads(_: SignalSet) -> io::Result<()> {
+    // The kqueue implementation ignores the signals, which is process-wide.
+    Ok(())
+}
+
+/// Set the signal action of the raw signal `signal` to `action`.
+fn set_signal_action(signal: libc::c_int, action: &libc::sigaction) -> io::Result<()> {
+    if unsafe { libc::sigaction(signal, action, ptr::null_mut()) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Convert the `si_code` of a signal into a `SignalCode`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn signal_code(code: libc::c_int) -> SignalCode {
+    match code {
+        libc::SI_USER => SignalCode::User,
+        libc::SI_KERNEL => SignalCode::Kernel,
+        libc::SI_QUEUE => SignalCode::Queue,
+        libc::SI_TKILL => SignalCode::Tkill,
+        code => SignalCode::Other(code),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn signal_code(code: libc::c_int) -> SignalCode {
+    // The `SI_*` constants are not defined by libc for these platforms.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos"
+    ))]
+    const SI: (libc::c_int, libc::c_int) = (0x10001, 0x10002);
+    #[cfg(target_os = "netbsd")]
+    const SI: (libc::c_int, libc::c_int) = (0, -1);
+    #[cfg(target_os = "openbsd")]
+    const SI: (libc::c_int, libc::c_int) = (0, -2);
+    const SI_USER: libc::c_int = SI.0;
+    const SI_QUEUE: libc::c_int = SI.1;
+
+    match code {
+        SI_USER => SignalCode::User,
+        SI_QUEUE => SignalCode::Queue,
+        code => SignalCode::Other(code),
+    }
+}
+
 /// Convert a `signal` into a Unix signal.
+///
+/// # Notes
+///
+/// `signal` must be checked using `check_signal`.
 fn raw_signal(signal: Signal) -> libc::c_int {
     match signal {
         Signal::Interrupt => libc::SIGINT,
@@ -47,6 +504,12 @@ fn raw_signal(signal: Signal) -> libc::c_int {
         Signal::Terminate => libc::SIGTERM,
         Signal::User1 => libc::SIGUSR1,
         Signal::User2 => libc::SIGUSR2,
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Child => libc::SIGCHLD,
+        Signal::Realtime(n) => match realtime_range() {
+            Some((min, _)) => min + libc::c_int::from(n),
+            None => unreachable!("real-time signals are not supported"),
+        },
     }
 }
 
@@ -58,7 +521,14 @@ fn from_raw_signal(raw_signal: libc::c_int) -> Option<Signal> {
         libc::SIGTERM => Some(Signal::Terminate),
         libc::SIGUSR1 => Some(Signal::User1),
         libc::SIGUSR2 => Some(Signal::User2),
-        _ => None,
+        libc::SIGHUP => Some(Signal::Hangup),
+        libc::SIGCHLD => Some(Signal::Child),
+        raw_signal => match realtime_range() {
+            Some((min, max)) if raw_signal >= min && raw_signal <= max => {
+                Some(Signal::Realtime((raw_signal - min) as u8))
+            }
+            _ => None,
+        },
     }
 }
 
@@ -69,6 +539,23 @@ fn test_from_raw_signal() {
     assert_eq!(from_raw_signal(libc::SIGTERM), Some(Signal::Terminate));
     assert_eq!(from_raw_signal(libc::SIGUSR1), Some(Signal::User1));
     assert_eq!(from_raw_signal(libc::SIGUSR2), Some(Signal::User2));
+    assert_eq!(from_raw_signal(libc::SIGHUP), Some(Signal::Hangup));
+    assert_eq!(from_raw_signal(libc::SIGCHLD), Some(Signal::Child));
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        assert_eq!(from_raw_signal(libc::SIGRTMIN()), Some(Signal::Realtime(0)));
+        assert_eq!(
+            from_raw_signal(libc::SIGRTMIN() + 2),
+            Some(Signal::Realtime(2))
+        );
+        let max = (libc::SIGRTMAX() - libc::SIGRTMIN()) as u8;
+        assert_eq!(
+            from_raw_signal(libc::SIGRTMAX()),
+            Some(Signal::Realtime(max))
+        );
+        assert_eq!(from_raw_signal(libc::SIGRTMAX() + 1), None);
+    }
 
     // Unsupported signals.
     assert_eq!(from_raw_signal(libc::SIGSTOP), None);
@@ -81,6 +568,32 @@ fn test_raw_signal() {
     assert_eq!(raw_signal(Signal::Terminate), libc::SIGTERM);
     assert_eq!(raw_signal(Signal::User1), libc::SIGUSR1);
     assert_eq!(raw_signal(Signal::User2), libc::SIGUSR2);
+    assert_eq!(raw_signal(Signal::Hangup), libc::SIGHUP);
+    assert_eq!(raw_signal(Signal::Child), libc::SIGCHLD);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        assert_eq!(raw_signal(Signal::Realtime(0)), libc::SIGRTMIN());
+        assert_eq!(raw_signal(Signal::Realtime(3)), libc::SIGRTMIN() + 3);
+    }
+}
+
+#[test]
+fn test_check_signal() {
+    assert!(check_signals(SignalSet::all()).is_ok());
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let max = (libc::SIGRTMAX() - libc::SIGRTMIN()) as u8;
+        assert!(check_signal(Signal::Realtime(0)).is_ok());
+        assert!(check_signal(Signal::Realtime(max)).is_ok());
+        let err = check_signal(Signal::Realtime(max + 1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let err = check_signal(Signal::Realtime(0)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
 }
 
 #[test]
@@ -105,4 +618,12 @@ fn raw_signal_round_trip() {
         raw_signal(from_raw_signal(libc::SIGUSR2).unwrap()),
         libc::SIGUSR2
     );
+    assert_eq!(
+        raw_signal(from_raw_signal(libc::SIGHUP).unwrap()),
+        libc::SIGHUP
+    );
+    assert_eq!(
+        raw_signal(from_raw_signal(libc::SIGCHLD).unwrap()),
+        libc::SIGCHLD
+    );
 }
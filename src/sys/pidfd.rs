@@ -0,0 +1,121 @@
+//! Process file descriptors, see [`pidfd_open(2)`].
+//!
+//! [`pidfd_open(2)`]: https://man7.org/linux/man-pages/man2/pidfd_open.2.html
+
+use std::mem::MaybeUninit;
+use std::os::unix::io::RawFd;
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+use std::{io, ptr};
+
+use crate::Signal;
+
+use super::{check_signal, raw_signal};
+
+/// Open a process file descriptor for the process `pid`.
+///
+/// Requires Linux 5.3, on older kernels this returns an error of kind
+/// `Unsupported`.
+pub fn pidfd_open(pid: u32) -> io::Result<RawFd> {
+    let flags: libc::c_uint = 0;
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, flags) };
+    if fd == -1 {
+        Err(unsupported(io::Error::last_os_error(), "pidfd_open", "5.3"))
+    } else {
+        let fd = fd as RawFd;
+        // Not all kernels support `PIDFD_NONBLOCK` or `O_CLOEXEC` as flag,
+        // so we set it manually.
+        match unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                let _ = unsafe { libc::close(fd) };
+                Err(err)
+            }
+            _ => Ok(fd),
+        }
+    }
+}
+
+/// Send `signal` to the process referred to by the process file descriptor
+/// `fd`.
+///
+/// Requires Linux 5.1, on older kernels this returns an error of kind
+/// `Unsupported`.
+pub fn pidfd_send_signal(fd: RawFd, signal: Signal) -> io::Result<()> {
+    check_signal(signal)?;
+    let info: *const libc::siginfo_t = ptr::null();
+    let flags: libc::c_uint = 0;
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_pidfd_send_signal,
+            fd,
+            raw_signal(signal),
+            info,
+            flags,
+        )
+    };
+    if res == -1 {
+        Err(unsupported(
+            io::Error::last_os_error(),
+            "pidfd_send_signal",
+            "5.1",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Wait for the child process referred to by the process file descriptor `fd`,
+/// without blocking, returning its exit status if it exited.
+///
+/// Requires Linux 5.4, on older kernels this returns an error of kind
+/// `Unsupported`.
+pub fn pidfd_wait(fd: RawFd) -> io::Result<Option<ExitStatus>> {
+    // Zeroed so we can detect if no child changed state, see `waitid(2)`.
+    let mut info: MaybeUninit<libc::siginfo_t> = MaybeUninit::zeroed();
+    let options = libc::WEXITED | libc::WNOHANG;
+    loop {
+        if unsafe { libc::waitid(libc::P_PIDFD, fd as libc::id_t, info.as_mut_ptr(), options) }
+            == -1
+        {
+            match io::Error::last_os_error() {
+                ref err if err.kind() == io::ErrorKind::Interrupted => continue,
+                // Kernels that don't support `P_PIDFD` return `EINVAL`.
+                ref err if err.raw_os_error() == Some(libc::EINVAL) => {
+                    let msg = "waitid(2) doesn't support P_PIDFD, requires Linux 5.4 or later";
+                    return Err(io::Error::new(io::ErrorKind::Unsupported, msg));
+                }
+                err => return Err(err),
+            }
+        }
+
+        // This is safe because `waitid` initialised it, or it's still zeroed.
+        let info = unsafe { info.assume_init() };
+        // Process hasn't exited yet.
+        if unsafe { info.si_pid() } == 0 {
+            return Ok(None);
+        }
+        // Convert into a status as returned by `waitpid(2)`.
+        let status = unsafe { info.si_status() };
+        let status = match info.si_code {
+            libc::CLD_EXITED => (status & 0xff) << 8,
+            libc::CLD_KILLED => status & 0x7f,
+            libc::CLD_DUMPED => (status & 0x7f) | 0x80,
+            _ => unreachable!("unexpected waitid code"),
+        };
+        return Ok(Some(ExitStatus::from_raw(status)));
+    }
+}
+
+/// Convert `ENOSYS` errors into a more descriptive error.
+fn unsupported(err: io::Error, syscall: &str, version: &str) -> io::Error {
+    if err.raw_os_error() == Some(libc::ENOSYS) {
+        let msg = format!(
+            "{}(2) is not supported by the kernel, requires Linux {} or later",
+            syscall, version
+        );
+        io::Error::new(io::ErrorKind::Unsupported, msg)
+    } else {
+        err
+    }
+}
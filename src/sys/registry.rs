@@ -0,0 +1,189 @@
+//! Process-wide registry of the signals in use by `Signals`.
+
+use std::io;
+use std::sync::{Mutex, PoisonError};
+
+use log::error;
+
+use crate::{Signal, SignalSet};
+
+/// Registry that reference counts the users of each signal.
+///
+/// Multiple `Signals` can listen for the same signal, but the signal mask (in
+/// the signalfd implementation) or signal action (in the kqueue
+/// implementation) should only be reverted once the last `Signals` using the
+/// signal is dropped. For each signal the registry keeps track of the number of
+/// users and the state `T` to restore once the last user is removed.
+pub struct SignalRegistry<T> {
+    entries: Mutex<Vec<Entry<T>>>,
+}
+
+struct Entry<T> {
+    signal: Signal,
+    /// Number of users of `signal`, always non-zero.
+    users: usize,
+    /// State to restore once the last user is removed.
+    state: T,
+}
+
+impl<T> SignalRegistry<T> {
+    /// Create a new, empty, registry.
+    pub const fn new() -> SignalRegistry<T> {
+        SignalRegistry {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a user for all `signals`.
+    ///
+    /// For signals that don't have any users yet `setup` is called, which must
+    /// return the state to pass to `restore` once the last user is removed. If
+    /// `setup` returns an error all signals added by this call are removed
+    /// again, calling `restore` for signals that were set up.
+    pub fn add<S, R>(&self, signals: SignalSet, mut setup: S, mut restore: R) -> io::Result<()>
+    where
+        S: FnMut(Signal) -> io::Result<T>,
+        R: FnMut(Signal, T) -> io::Result<()>,
+    {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        for (n, signal) in signals.into_iter().enumerate() {
+            if let Some(entry) = entries.iter_mut().find(|e| e.signal == signal) {
+                entry.users += 1;
+                continue;
+            }
+
+            match setup(signal) {
+                Ok(state) => entries.push(Entry {
+                    signal,
+                    users: 1,
+                    state,
+                }),
+                Err(err) => {
+                    // Remove the signals we already added.
+                    let added = signals.into_iter().take(n);
+                    if let Err(err) = remove(&mut entries, added, &mut restore) {
+                        error!("error restoring signal state: {}", err);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if `signal` has any users.
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(
+            target_arch = "aarch64",
+            target_arch = "arm",
+            target_arch = "x86",
+            target_arch = "x86_64"
+        ),
+        not(target_env = "uclibc")
+    ))]
+    pub fn contains(&self, signal: Signal) -> bool {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.iter().any(|e| e.signal == signal)
+    }
+
+    /// Remove a user for all `signals`.
+    ///
+    /// For signals without any remaining users `restore` is called with the
+    /// state returned by `setup` in [`SignalRegistry::add`]. If `restore` returns an
+    /// error the other signals are still removed, the first error is returned.
+    pub fn remove<R>(&self, signals: SignalSet, mut restore: R) -> io::Result<()>
+    where
+        R: FnMut(Signal, T) -> io::Result<()>,
+    {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        remove(&mut entries, signals.into_iter(), &mut restore)
+    }
+}
+
+fn remove<T, I, R>(entries: &mut Vec<Entry<T>>, signals: I, restore: &mut R) -> io::Result<()>
+where
+    I: Iterator<Item = Signal>,
+    R: FnMut(Signal, T) -> io::Result<()>,
+{
+    let mut result = Ok(());
+    for signal in signals {
+        let idx = match entries.iter().position(|e| e.signal == signal) {
+            Some(idx) => idx,
+            None => continue,
+        };
+
+        entries[idx].users -= 1;
+        if entries[idx].users == 0 {
+            let entry = entries.swap_remove(idx);
+            let res = restore(entry.signal, entry.state);
+            if result.is_ok() {
+                result = res;
+            }
+        }
+    }
+    result
+}
+
+#[test]
+fn registry() {
+    let registry: SignalRegistry<usize> = SignalRegistry::new();
+    let mut setups = Vec::new();
+    let mut restores = Vec::new();
+
+    let set1 = Signal::Interrupt | Signal::Terminate;
+    let set2 = Signal::Terminate | Signal::Quit;
+    let mut setup = |signal, state| {
+        setups.push(signal);
+        Ok(state)
+    };
+    registry
+        .add(set1, |signal| setup(signal, 1), |_, _| Ok(()))
+        .unwrap();
+    registry
+        .add(set2, |signal| setup(signal, 2), |_, _| Ok(()))
+        .unwrap();
+    // `Terminate` is shared, so it's only set up once.
+    assert_eq!(
+        setups,
+        vec![Signal::Interrupt, Signal::Terminate, Signal::Quit]
+    );
+
+    let mut restore = |signal, state| {
+        restores.push((signal, state));
+        Ok(())
+    };
+    registry.remove(set1, &mut restore).unwrap();
+    registry.remove(set2, &mut restore).unwrap();
+    // `Terminate` is restored only after the last user is removed, using the
+    // state of the first user.
+    assert_eq!(
+        restores,
+        vec![
+            (Signal::Interrupt, 1),
+            (Signal::Quit, 2),
+            (Signal::Terminate, 1)
+        ]
+    );
+}
+
+#[test]
+fn registry_setup_error() {
+    let registry: SignalRegistry<()> = SignalRegistry::new();
+    let mut restores = Vec::new();
+
+    let set = Signal::Interrupt | Signal::Quit | Signal::Terminate;
+    let setup = |signal| match signal {
+        Signal::Terminate => Err(io::Error::new(io::ErrorKind::Other, "oops")),
+        _ => Ok(()),
+    };
+    let restore = |signal, ()| {
+        restores.push(signal);
+        Ok(())
+    };
+    let err = registry.add(set, setup, restore).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    // Signals set up before the error are restored.
+    assert_eq!(restores, vec![Signal::Interrupt, Signal::Quit]);
+    assert!(registry.entries.lock().unwrap().is_empty());
+}
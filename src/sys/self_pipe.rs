@@ -0,0 +1,414 @@
+//! Self-pipe implementation of `Signals`, see the `Signals` type.
+
+use std::cmp::min;
+use std::mem::{size_of, MaybeUninit};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use std::{fmt, io, thread};
+
+use log::error;
+use mio::unix::SourceFd;
+use mio::{event, Interest, Registry, Token};
+
+use crate::{Signal, SignalCode, SignalInfo, SignalSet};
+
+use super::registry::SignalRegistry;
+use super::{
+    from_raw_signal, poll_readable, raw_signal, set_signal_action, set_signal_handler, signal_code,
+};
+
+/// Registry of the signals we installed a signal handler for, keeping track of
+/// the previous signal action.
+static REGISTRY: SignalRegistry<libc::sigaction> = SignalRegistry::new();
+
+/// Maximum number of `Signals` using the self-pipe at the same time.
+const MAX_PIPES: usize = 64;
+
+/// Unused pipe, used to initialise `PIPES`. This is only ever copied into
+/// `PIPES`, never used directly.
+#[allow(clippy::declare_interior_mutable_const)]
+const PIPE: Pipe = Pipe::new();
+
+/// Pipes the signal handler writes to.
+static PIPES: [Pipe; MAX_PIPES] = [PIPE; MAX_PIPES];
+
+/// Number of signal handlers currently running.
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Value of `Pipe::fd` if the pipe is unused.
+const UNUSED: RawFd = -1;
+/// Value of `Pipe::fd` if the pipe is being set up or torn down.
+const RESERVED: RawFd = -2;
+
+/// Write end of a pipe, as seen by the signal handler.
+struct Pipe {
+    /// Write end of the pipe, or `UNUSED` or `RESERVED`.
+    fd: AtomicI32,
+    /// Signals the pipe is interested in, bit `n - 1` is set for raw signal
+    /// `n`.
+    signals: AtomicU64,
+}
+
+impl Pipe {
+    const fn new() -> Pipe {
+        Pipe {
+            fd: AtomicI32::new(UNUSED),
+            signals: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Signaler backed by a signal handler writing to a pipe.
+///
+/// # Implementation notes
+///
+/// We install a signal handler, using `sigaction(2)` with `SA_SIGINFO`, for all
+/// signals. The signal handler writes a `Record` to the write end of the pipe
+/// of every `Signals` interested in the signal (see `PIPES`). The read end of
+/// the pipe is registered with `Poll`, from which we read the records in
+/// `receive_info`.
+///
+/// Because signal handlers are process-wide this works regardless of which
+/// thread the signal is delivered to, as long as the signal isn't blocked in
+/// all threads.
+///
+/// Both ends of the pipe are non-blocking. If the pipe is full the signal is
+/// dropped, similar to how standard signals are merged by the kernel.
+///
+/// Multiple `Signals` can use the same signal, so we only restore the signal
+/// action once the last `Signals` using it is dropped (see `REGISTRY`).
+pub struct Signals {
+    /// Read end of the pipe.
+    fd: RawFd,
+    /// Index into `PIPES`.
+    index: usize,
+    /// All signals this is listening for, used in resetting the signal handlers.
+    signals: SignalSet,
+    /// Whether or not to restore the signal actions when dropped.
+    restore: bool,
+}
+
+impl Signals {
+    pub fn new(signals: SignalSet) -> io::Result<Signals> {
+        let (read_fd, write_fd) = new_pipe()?;
+        let index = match add_pipe(write_fd, signals) {
+            Ok(index) => index,
+            Err(err) => {
+                // Don't leak the file descriptors. Can't do anything about
+                // errors here.
+                let _ = unsafe { libc::close(read_fd) };
+                let _ = unsafe { libc::close(write_fd) };
+                return Err(err);
+            }
+        };
+        match REGISTRY.add(signals, install_handler, restore_handler) {
+            Ok(()) => Ok(Signals {
+                fd: read_fd,
+                index,
+                signals,
+                restore: true,
+            }),
+            Err(err) => {
+                remove_pipe(index);
+                let _ = unsafe { libc::close(read_fd) };
+                Err(err)
+            }
+        }
+    }
+
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
+        let mut records = [MaybeUninit::uninit()];
+        read_records(self.fd, &mut records).map(|records| records.first().and_then(signal_info))
+    }
+
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        let mut records = [MaybeUninit::uninit(); BATCH_SIZE];
+        let max = min(infos.len(), BATCH_SIZE);
+        let records = read_records(self.fd, &mut records[..max])?;
+        let mut n = 0;
+        for info in records.iter().filter_map(signal_info) {
+            infos[n] = info;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        poll_readable(&[self.fd], timeout)
+    }
+
+    pub fn set_restore(&mut self, restore: bool) {
+        self.restore = restore;
+    }
+}
+
+/// Information about a received signal, written to the pipe by the signal
+/// handler.
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Record {
+    signal: libc::c_int,
+    code: libc::c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    status: libc::c_int,
+    value: u64,
+}
+
+impl Record {
+    /// Create a new record for `signal`, using the information in `info`.
+    ///
+    /// # Notes
+    ///
+    /// This is called in the signal handler, so it must be async-signal-safe.
+    fn new(signal: libc::c_int, info: Option<&libc::siginfo_t>) -> Record {
+        let mut record = Record {
+            signal,
+            code: 0,
+            pid: 0,
+            uid: 0,
+            status: 0,
+            value: 0,
+        };
+        if let Some(info) = info {
+            record.code = info.si_code;
+            unsafe {
+                record.pid = info.si_pid();
+                record.uid = info.si_uid();
+                record.status = info.si_status();
+                record.value = info.si_value().sival_ptr as u64;
+            }
+        }
+        record
+    }
+}
+
+/// Maximum number of `Record`s to read at once.
+const BATCH_SIZE: usize = 32;
+
+/// Signal handler for all signals, see `Signals`.
+///
+/// # Safety
+///
+/// This may only call async-signal-safe functions, see `signal-safety(7)`.
+extern "C" fn handle_signal(signal: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    // `write(2)` can overwrite `errno`, which would confuse the interrupted
+    // code, so we restore it at the end.
+    let errno = unsafe { *errno_location() };
+    let _ = RUNNING.fetch_add(1, Ordering::SeqCst);
+
+    // Should never be null as we use `SA_SIGINFO`, but just in case.
+    let record = Record::new(signal, unsafe { info.as_ref() });
+
+    let bit = signal_bit(signal);
+    for pipe in PIPES.iter() {
+        let fd = pipe.fd.load(Ordering::SeqCst);
+        if fd >= 0 && pipe.signals.load(Ordering::SeqCst) & bit != 0 {
+            // If the pipe is full we drop the signal, nothing else we can do.
+            let record: *const Record = &record;
+            let _ = unsafe { libc::write(fd, record.cast(), size_of::<Record>()) };
+        }
+    }
+
+    let _ = RUNNING.fetch_sub(1, Ordering::SeqCst);
+    unsafe { *errno_location() = errno };
+}
+
+#[cfg(any(target_os = "linux", target_os = "dragonfly"))]
+use libc::__errno_location as errno_location;
+
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+use libc::__errno as errno_location;
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+use libc::__error as errno_location;
+
+/// Returns the bit in `Pipe::signals` for the raw signal `signal`.
+const fn signal_bit(signal: libc::c_int) -> u64 {
+    // Signal numbers start at one and are at most 64 on all platforms we
+    // support.
+    1 << ((signal - 1) as u64 & 63)
+}
+
+/// Create a new non-blocking pipe, returning the read and write end.
+fn new_pipe() -> io::Result<(RawFd, RawFd)> {
+    let mut fds: [RawFd; 2] = [-1, -1];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // Not using `pipe2(2)` as it's not available on all platforms.
+    for fd in fds {
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1
+            || unsafe { libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK) } == -1
+        {
+            let err = io::Error::last_os_error();
+            let _ = unsafe { libc::close(fds[0]) };
+            let _ = unsafe { libc::close(fds[1]) };
+            return Err(err);
+        }
+    }
+    Ok((fds[0], fds[1]))
+}
+
+/// Add the write end of a pipe, `fd`, to `PIPES`, returning its index.
+fn add_pipe(fd: RawFd, signals: SignalSet) -> io::Result<usize> {
+    let bits = signals
+        .into_iter()
+        .fold(0, |bits, signal| bits | signal_bit(raw_signal(signal)));
+    for (index, pipe) in PIPES.iter().enumerate() {
+        if pipe
+            .fd
+            .compare_exchange(UNUSED, RESERVED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            // Set the signals before the file descriptor, so that the signal
+            // handler never sees the signals of a previous user.
+            pipe.signals.store(bits, Ordering::SeqCst);
+            pipe.fd.store(fd, Ordering::SeqCst);
+            return Ok(index);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "too many self-pipe Signals in use",
+    ))
+}
+
+/// Remove the pipe at `index` from `PIPES`, closing the write end.
+fn remove_pipe(index: usize) {
+    let pipe = &PIPES[index];
+    let fd = pipe.fd.swap(RESERVED, Ordering::SeqCst);
+    pipe.signals.store(0, Ordering::SeqCst);
+    // Wait for running signal handlers, which might still use `fd`. Signal
+    // handlers that start after the swap above will skip the pipe.
+    while RUNNING.load(Ordering::SeqCst) != 0 {
+        thread::yield_now();
+    }
+    close(fd, "Signals pipe");
+    pipe.fd.store(UNUSED, Ordering::SeqCst);
+}
+
+/// Read multiple `Record`s from the pipe `fd`, using a single `read(2)` call.
+/// Returns the `Record`s read.
+fn read_records(fd: RawFd, records: &mut [MaybeUninit<Record>]) -> io::Result<&[Record]> {
+    const RECORD_SIZE: usize = size_of::<Record>();
+    loop {
+        let n = unsafe { libc::read(fd, records.as_mut_ptr().cast(), records.len() * RECORD_SIZE) };
+        match n {
+            -1 => match io::Error::last_os_error() {
+                ref err if err.kind() == io::ErrorKind::WouldBlock => return Ok(&[]),
+                ref err if err.kind() == io::ErrorKind::Interrupted => continue,
+                err => return Err(err),
+            },
+            // Writes up to `PIPE_BUF` bytes are atomic, so we always read
+            // complete records.
+            // `usize::is_multiple_of` requires a newer Rust version.
+            #[allow(clippy::manual_is_multiple_of)]
+            n if n as usize % RECORD_SIZE == 0 => {
+                // This is safe because we just read into the first `n`
+                // records.
+                let n = n as usize / RECORD_SIZE;
+                return Ok(unsafe { &*(&records[..n] as *const [_] as *const [_]) });
+            }
+            _ => unreachable!("read an incorrect amount of bytes from pipe"),
+        }
+    }
+}
+
+/// Convert `info`, e.g. as returned by `sigwaitinfo(2)`, into `SignalInfo`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn siginfo_signal_info(info: &libc::siginfo_t) -> Option<SignalInfo> {
+    signal_info(&Record::new(info.si_signo, Some(info)))
+}
+
+/// Convert `record` into `SignalInfo`.
+fn signal_info(record: &Record) -> Option<SignalInfo> {
+    let signal = from_raw_signal(record.signal)?;
+    let code = signal_code(record.code);
+    let mut signal_info = SignalInfo::new(signal);
+    signal_info.code = Some(code);
+    signal_info.pid = Some(record.pid as u32);
+    signal_info.uid = Some(record.uid);
+    if let SignalCode::Queue = code {
+        signal_info.value = Some(record.value);
+    }
+    if let Signal::Child = signal {
+        signal_info.child_status = Some(record.status);
+    }
+    Some(signal_info)
+}
+
+/// Install our signal handler for `signal`, returning the previous signal
+/// action.
+fn install_handler(signal: Signal) -> io::Result<libc::sigaction> {
+    set_signal_handler(raw_signal(signal), handle_signal)
+}
+
+/// Inverse of `install_handler`, restoring the signal action to `old_action`.
+fn restore_handler(signal: Signal, old_action: libc::sigaction) -> io::Result<()> {
+    set_signal_action(raw_signal(signal), &old_action)
+}
+
+/// Close `fd`, logging errors.
+fn close(fd: RawFd, what: &str) {
+    if unsafe { libc::close(fd) } == -1 {
+        // See the signalfd implementation for possible errors.
+        let err = io::Error::last_os_error();
+        error!("error closing {}: {}", what, err);
+    }
+}
+
+impl event::Source for Signals {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.fd).deregister(registry)
+    }
+}
+
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl fmt::Debug for Signals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signals").field("fd", &self.fd).finish()
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        // Restore the signal actions. Even if we don't restore them we still
+        // need to remove our use of the signals.
+        let res = if self.restore {
+            REGISTRY.remove(self.signals, restore_handler)
+        } else {
+            REGISTRY.remove(self.signals, |_, _| Ok(()))
+        };
+        if let Err(err) = res {
+            error!("error resetting signal action: {}", err);
+        }
+
+        remove_pipe(self.index);
+        close(self.fd, "Signals");
+    }
+}
@@ -1,14 +1,35 @@
+use std::cmp::min;
 use std::mem::{size_of, MaybeUninit};
-use std::os::unix::io::RawFd;
-use std::{fmt, io, ptr};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
+use std::{fmt, fs, io, ptr};
 
 use log::error;
 use mio::unix::SourceFd;
 use mio::{event, Interest, Registry, Token};
 
-use crate::{Signal, SignalSet};
+use crate::{Signal, SignalCode, SignalInfo, SignalSet, UnblockedThread};
 
-use super::{from_raw_signal, raw_signal};
+use super::registry::SignalRegistry;
+use super::{from_raw_signal, poll_readable, raw_signal, signal_code};
+
+mod fan_out;
+// Requires `ucontext_t.uc_sigmask`, which libc doesn't define for all
+// architectures.
+#[cfg(all(
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "x86",
+        target_arch = "x86_64"
+    ),
+    not(target_env = "uclibc")
+))]
+mod threads;
+
+/// Registry of the signals we blocked, keeping track whether or not the signal
+/// was already blocked before.
+static REGISTRY: SignalRegistry<bool> = SignalRegistry::new();
 
 /// Signaler backed by `signalfd(2)`.
 ///
@@ -24,59 +45,264 @@ use super::{from_raw_signal, raw_signal};
 /// We can't ignore the signal using `SIG_IGN`, like we do in the kqueue
 /// implementation, because then the signals don't end up in our `signalfd`
 /// either.
+///
+/// Multiple `Signals` can use the same signal, so we only unblock a signal once
+/// the last `Signals` using it is dropped (see `REGISTRY`). Signals that were
+/// already blocked before the first `Signals` was created are never unblocked.
+/// Note that the blocked signals are a per thread property, while the registry
+/// is process-wide, this is why `Signals` should be created on the main thread.
+///
+/// In fan-out mode all `Signals` share a single `signalfd`, see the `fan_out`
+/// module.
 pub struct Signals {
-    /// `signalfd(2)` file descriptor.
+    /// `signalfd(2)` file descriptor. In fan-out mode this is an `epoll(7)`
+    /// file descriptor instead, see the `fan_out` module.
     fd: RawFd,
-    /// All signals this is listening for, used in resetting the signal handlers.
-    signals: libc::sigset_t,
+    /// Whether or not this is in fan-out mode.
+    fan_out: bool,
+    /// All signals this is listening for, used in resetting the signal mask.
+    signals: SignalSet,
+    /// Whether or not to unblock the signals when dropped.
+    restore: bool,
 }
 
 impl Signals {
     pub fn new(signals: SignalSet) -> io::Result<Signals> {
         create_sigset(signals)
             .and_then(|set| new_signalfd(&set).map(|fd| (fd, set)))
-            .map(|(fd, set)| (Signals { fd, signals: set }, set))
-            .and_then(|(fd, set)| block_signals(&set).map(|()| fd))
-    }
-
-    pub fn receive(&mut self) -> io::Result<Option<Signal>> {
-        let mut info: MaybeUninit<libc::signalfd_siginfo> = MaybeUninit::uninit();
-
-        loop {
-            let n = unsafe {
-                libc::read(
-                    self.fd,
-                    info.as_mut_ptr().cast(),
-                    size_of::<libc::signalfd_siginfo>(),
-                )
-            };
-
-            const INFO_SIZE: isize = size_of::<libc::signalfd_siginfo>() as isize;
-            match n {
-                -1 => match io::Error::last_os_error() {
-                    ref err if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
-                    ref err if err.kind() == io::ErrorKind::Interrupted => continue,
-                    err => return Err(err),
-                },
-                INFO_SIZE => {
-                    // This is safe because we just read into it.
-                    let info = unsafe { info.assume_init() };
-                    return Ok(from_raw_signal(info.ssi_signo as libc::c_int));
+            .and_then(|(fd, set)| match block_signals(signals, &set) {
+                Ok(()) => Ok(Signals {
+                    fd,
+                    fan_out: false,
+                    signals,
+                    restore: true,
+                }),
+                Err(err) => {
+                    // Don't leak the file descriptor. Can't do anything about
+                    // errors here.
+                    let _ = unsafe { libc::close(fd) };
+                    Err(err)
                 }
-                _ => unreachable!("read an incorrect amount of bytes from signalfd"),
+            })
+    }
+
+    pub fn new_fan_out(signals: SignalSet) -> io::Result<Signals> {
+        create_sigset(signals)
+            .and_then(|set| fan_out::subscribe(signals).map(|fd| (fd, set)))
+            .and_then(|(fd, set)| match block_signals(signals, &set) {
+                Ok(()) => Ok(Signals {
+                    fd,
+                    fan_out: true,
+                    signals,
+                    restore: true,
+                }),
+                Err(err) => {
+                    fan_out::unsubscribe(fd);
+                    Err(err)
+                }
+            })
+    }
+
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
+        if self.fan_out {
+            return fan_out::receive(self.fd);
+        }
+
+        let mut raw_info = [MaybeUninit::uninit()];
+        read_siginfos(self.fd, &mut raw_info).map(|infos| infos.first().and_then(signal_info))
+    }
+
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        if self.fan_out {
+            return fan_out::receive_batch(self.fd, infos);
+        }
+
+        let mut raw_infos = [MaybeUninit::uninit(); BATCH_SIZE];
+        let max = min(infos.len(), BATCH_SIZE);
+        let raw_infos = read_siginfos(self.fd, &mut raw_infos[..max])?;
+        let mut n = 0;
+        for info in raw_infos.iter().filter_map(signal_info) {
+            infos[n] = info;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        poll_readable(&[self.fd], timeout)
+    }
+
+    pub fn check_thread_masks(&self) -> io::Result<Vec<UnblockedThread>> {
+        unblocked_threads(self.signals)
+    }
+
+    pub fn set_restore(&mut self, restore: bool) {
+        self.restore = restore;
+    }
+}
+
+/// Returns all threads that don't block all `signals`.
+pub fn unblocked_threads(signals: SignalSet) -> io::Result<Vec<UnblockedThread>> {
+    let mut threads = Vec::new();
+    for entry in fs::read_dir("/proc/self/task")? {
+        let entry = entry?;
+        let tid = match entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+            Some(tid) => tid,
+            None => continue,
+        };
+        let status = match fs::read_to_string(entry.path().join("status")) {
+            Ok(status) => status,
+            // Thread stopped.
+            Err(ref err)
+                if err.kind() == io::ErrorKind::NotFound
+                    || err.raw_os_error() == Some(libc::ESRCH) =>
+            {
+                continue
             }
+            Err(err) => return Err(err),
+        };
+        if let Some(thread) = unblocked_thread(tid, &status, signals)? {
+            threads.push(thread);
         }
     }
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
 }
 
-/// Create a `libc::sigset_t` from `SignalSet`.
-fn create_sigset(signals: SignalSet) -> io::Result<libc::sigset_t> {
-    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
-    if unsafe { libc::sigemptyset(set.as_mut_ptr()) } == -1 {
-        return Err(io::Error::last_os_error());
+/// Parse the `/proc/self/task/[tid]/status` file of thread `tid`, returning
+/// the thread if it doesn't block all `signals`. See `proc(5)` for the format.
+fn unblocked_thread(
+    tid: u32,
+    status: &str,
+    signals: SignalSet,
+) -> io::Result<Option<UnblockedThread>> {
+    let mut name = None;
+    let mut blocked = None;
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("SigBlk:") {
+            blocked = u64::from_str_radix(value.trim(), 16).ok();
+        }
+    }
+    let blocked = blocked.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing or invalid SigBlk in thread status",
+        )
+    })?;
+
+    // Bit `n - 1` is set if raw signal `n` is blocked.
+    let unblocked = signals
+        .into_iter()
+        .filter(|signal| blocked & 1 << (raw_signal(*signal) - 1) == 0)
+        .fold(None, |set: Option<SignalSet>, signal| match set {
+            Some(set) => Some(set | signal),
+            None => Some(signal.into()),
+        });
+    Ok(unblocked.map(|signals| UnblockedThread {
+        tid,
+        name: name.unwrap_or_default().to_owned(),
+        signals,
+    }))
+}
+
+/// Block `signals` in all threads of the process, see the `threads` module.
+#[cfg(all(
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "x86",
+        target_arch = "x86_64"
+    ),
+    not(target_env = "uclibc")
+))]
+pub fn block_in_all_threads(signals: SignalSet) -> io::Result<()> {
+    threads::block_in_all_threads(signals)
+}
+
+#[cfg(not(all(
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "x86",
+        target_arch = "x86_64"
+    ),
+    not(target_env = "uclibc")
+)))]
+pub fn block_in_all_threads(_: SignalSet) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "blocking signals in all threads is not supported on this architecture",
+    ))
+}
+
+/// Maximum number of `signalfd_siginfo`s to read at once.
+const BATCH_SIZE: usize = 32;
+
+/// Read multiple `signalfd_siginfo`s from the signalfd `fd`, using a single
+/// `read(2)` call. Returns the `signalfd_siginfo`s read.
+fn read_siginfos(
+    fd: RawFd,
+    infos: &mut [MaybeUninit<libc::signalfd_siginfo>],
+) -> io::Result<&[libc::signalfd_siginfo]> {
+    const INFO_SIZE: usize = size_of::<libc::signalfd_siginfo>();
+    loop {
+        let n = unsafe { libc::read(fd, infos.as_mut_ptr().cast(), infos.len() * INFO_SIZE) };
+        match n {
+            -1 => match io::Error::last_os_error() {
+                ref err if err.kind() == io::ErrorKind::WouldBlock => return Ok(&[]),
+                ref err if err.kind() == io::ErrorKind::Interrupted => continue,
+                err => return Err(err),
+            },
+            // `usize::is_multiple_of` requires a newer Rust version.
+            #[allow(clippy::manual_is_multiple_of)]
+            n if n as usize % INFO_SIZE == 0 => {
+                // This is safe because we just read into the first `n`
+                // infos.
+                let n = n as usize / INFO_SIZE;
+                return Ok(unsafe { &*(&infos[..n] as *const [_] as *const [_]) });
+            }
+            _ => unreachable!("read an incorrect amount of bytes from signalfd"),
+        }
     }
-    // This is safe because `sigemptyset` ensures `set` is initialised.
-    let mut set = unsafe { set.assume_init() };
+}
+
+/// Convert `info` into `SignalInfo`.
+fn signal_info(info: &libc::signalfd_siginfo) -> Option<SignalInfo> {
+    let signal = from_raw_signal(info.ssi_signo as libc::c_int)?;
+    let code = signal_code(info.ssi_code);
+    let mut signal_info = SignalInfo::new(signal);
+    signal_info.code = Some(code);
+    signal_info.pid = Some(info.ssi_pid);
+    signal_info.uid = Some(info.ssi_uid);
+    if let SignalCode::Queue = code {
+        signal_info.value = Some(info.ssi_ptr);
+    }
+    if let Signal::Child = signal {
+        signal_info.child_status = Some(info.ssi_status);
+        signal_info.child_user_time = Some(clock_ticks(info.ssi_utime));
+        signal_info.child_system_time = Some(clock_ticks(info.ssi_stime));
+    }
+    Some(signal_info)
+}
+
+/// Convert a number of clock ticks into a duration.
+fn clock_ticks(ticks: u64) -> Duration {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        // Shouldn't happen, but don't want to divide by zero.
+        ticks_per_second if ticks_per_second <= 0 => Duration::from_secs(0),
+        ticks_per_second => {
+            let ticks_per_second = ticks_per_second as u64;
+            let nanos = (ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second;
+            Duration::new(ticks / ticks_per_second, nanos as u32)
+        }
+    }
+}
+
+/// Create a `libc::sigset_t` from `SignalSet`.
+pub fn create_sigset(signals: SignalSet) -> io::Result<libc::sigset_t> {
+    let mut set = empty_sigset()?;
     for signal in signals {
         if unsafe { libc::sigaddset(&mut set, raw_signal(signal)) } == -1 {
             return Err(io::Error::last_os_error());
@@ -85,6 +311,17 @@ fn create_sigset(signals: SignalSet) -> io::Result<libc::sigset_t> {
     Ok(set)
 }
 
+/// Create an empty `sigset_t`.
+pub fn empty_sigset() -> io::Result<libc::sigset_t> {
+    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    if unsafe { libc::sigemptyset(set.as_mut_ptr()) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        // This is safe because `sigemptyset` ensures `set` is initialised.
+        Ok(unsafe { set.assume_init() })
+    }
+}
+
 fn new_signalfd(set: &libc::sigset_t) -> io::Result<RawFd> {
     let fd = unsafe { libc::signalfd(-1, set, libc::SFD_CLOEXEC | libc::SFD_NONBLOCK) };
     if fd == -1 {
@@ -94,18 +331,98 @@ fn new_signalfd(set: &libc::sigset_t) -> io::Result<RawFd> {
     }
 }
 
-/// Block all signals in `set`.
-fn block_signals(set: &libc::sigset_t) -> io::Result<()> {
-    sigprocmask(libc::SIG_BLOCK, set)
+/// Create a new non-blocking `eventfd(2)`.
+pub fn new_eventfd() -> io::Result<RawFd> {
+    match unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) } {
+        -1 => Err(io::Error::last_os_error()),
+        fd => Ok(fd),
+    }
 }
 
-/// Inverse of `block_signals`, unblock all signals in `set`.
-fn unblock_signals(set: &libc::sigset_t) -> io::Result<()> {
-    sigprocmask(libc::SIG_UNBLOCK, set)
+/// Make `eventfd` readable.
+pub fn notify_eventfd(eventfd: RawFd) -> io::Result<()> {
+    let value: u64 = 1;
+    let n = unsafe { libc::write(eventfd, (&value as *const u64).cast(), size_of::<u64>()) };
+    match n {
+        -1 => match io::Error::last_os_error() {
+            // Counter is at its maximum, so it's already readable.
+            ref err if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            err => Err(err),
+        },
+        _ => Ok(()),
+    }
 }
 
-fn sigprocmask(how: libc::c_int, set: &libc::sigset_t) -> io::Result<()> {
-    let errno = unsafe { libc::pthread_sigmask(how, set, ptr::null_mut()) };
+/// Reset `eventfd` so it's no longer readable.
+pub fn reset_eventfd(eventfd: RawFd) -> io::Result<()> {
+    let mut value: u64 = 0;
+    let n = unsafe { libc::read(eventfd, (&mut value as *mut u64).cast(), size_of::<u64>()) };
+    match n {
+        -1 => match io::Error::last_os_error() {
+            // Not notified.
+            ref err if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            err => Err(err),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Close `fd`, logging errors.
+pub fn close(fd: RawFd, what: &str) {
+    if unsafe { libc::close(fd) } == -1 {
+        // Possible errors:
+        // - EBADF, EIO: can't recover.
+        // - EINTR: could try again but we're can't be sure if the file
+        //          descriptor was closed or not, so to be safe we don't
+        //          close it again.
+        let err = io::Error::last_os_error();
+        error!("error closing {}: {}", what, err);
+    }
+}
+
+/// Block all `signals`, `set` must be created from `signals`.
+///
+/// The signals are always blocked in the current thread, even if another
+/// `Signals` already uses the signal.
+pub fn block_signals(signals: SignalSet, set: &libc::sigset_t) -> io::Result<()> {
+    let mut old_set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    sigprocmask(libc::SIG_BLOCK, set, old_set.as_mut_ptr())?;
+    // This is safe because `pthread_sigmask` initialised the set.
+    let old_set = unsafe { old_set.assume_init() };
+    REGISTRY.add(
+        signals,
+        |signal| Ok(unsafe { libc::sigismember(&old_set, raw_signal(signal)) } == 1),
+        unblock_signal,
+    )
+}
+
+/// Inverse of `block_signals`, unblock all `signals` that are no longer used.
+pub fn unblock_signals(signals: SignalSet) -> io::Result<()> {
+    REGISTRY.remove(signals, unblock_signal)
+}
+
+/// Remove all `signals` from the registry without unblocking them, leaving them
+/// for the last `Signals` using them to unblock.
+pub fn forget_signals(signals: SignalSet) -> io::Result<()> {
+    REGISTRY.remove(signals, |_, _| Ok(()))
+}
+
+/// Unblock `signal`, if it wasn't already blocked before.
+fn unblock_signal(signal: Signal, was_blocked: bool) -> io::Result<()> {
+    if was_blocked {
+        Ok(())
+    } else {
+        let set = create_sigset(signal.into())?;
+        sigprocmask(libc::SIG_UNBLOCK, &set, ptr::null_mut())
+    }
+}
+
+pub fn sigprocmask(
+    how: libc::c_int,
+    set: &libc::sigset_t,
+    old_set: *mut libc::sigset_t,
+) -> io::Result<()> {
+    let errno = unsafe { libc::pthread_sigmask(how, set, old_set) };
     if errno == 0 {
         Ok(())
     } else {
@@ -137,6 +454,12 @@ impl event::Source for Signals {
     }
 }
 
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
 impl fmt::Debug for Signals {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Signals").field("fd", &self.fd).finish()
@@ -146,18 +469,20 @@ impl fmt::Debug for Signals {
 impl Drop for Signals {
     fn drop(&mut self) {
         // Reverse the blocking of signals.
-        if let Err(err) = unblock_signals(&self.signals) {
+        let res = if self.restore {
+            unblock_signals(self.signals)
+        } else {
+            forget_signals(self.signals)
+        };
+        if let Err(err) = res {
             error!("error unblocking signals: {}", err);
         }
 
-        if unsafe { libc::close(self.fd) } == -1 {
-            // Possible errors:
-            // - EBADF, EIO: can't recover.
-            // - EINTR: could try again but we're can't be sure if the file
-            //          descriptor was closed or not, so to be safe we don't
-            //          close it again.
-            let err = io::Error::last_os_error();
-            error!("error closing Signals: {}", err);
+        if self.fan_out {
+            // Closes the file descriptors.
+            fan_out::unsubscribe(self.fd);
+        } else {
+            close(self.fd, "Signals");
         }
     }
 }
@@ -0,0 +1,270 @@
+//! Fan-out mode of `Signals`, see `Signals::new_fan_out`.
+//!
+//! # Implementation notes
+//!
+//! A signal is removed from the signal queue once it's read from a `signalfd`,
+//! so multiple `signalfd`s can't each receive a copy of the same signal. To
+//! support multiple `Signals` receiving the same signal all `Signals` in
+//! fan-out mode share a single `signalfd`, the `Hub`. Whichever `Signals`
+//! reads from the hub first copies all signals into the queues of all
+//! interested subscribers.
+//!
+//! Since the signals are read by another `Signals` the `signalfd` might no
+//! longer be readable when the subscriber is polled, so each subscriber also
+//! has an `eventfd(2)` which is written to when a signal is added to its queue.
+//! Both a duplicate of the shared `signalfd` and the `eventfd` are added to an
+//! `epoll(7)` instance, which is the file descriptor used by `Signals`. This
+//! way `Signals` only has a single file descriptor to poll.
+
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::unix::io::RawFd;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use log::error;
+
+use crate::{Signal, SignalInfo, SignalSet};
+
+use super::{
+    close, create_sigset, new_eventfd, new_signalfd, notify_eventfd, read_siginfos, reset_eventfd,
+    signal_info, BATCH_SIZE,
+};
+
+/// Maximum number of signals queued per subscriber, signals received once the
+/// queue is full are dropped.
+const MAX_QUEUED: usize = 1024;
+
+/// The shared `signalfd`, `None` if there are no subscribers.
+static HUB: Mutex<Option<Hub>> = Mutex::new(None);
+
+struct Hub {
+    /// Shared `signalfd(2)` file descriptor, its mask is the union of the
+    /// signals of all subscribers.
+    fd: RawFd,
+    subscribers: Vec<Subscriber>,
+}
+
+struct Subscriber {
+    /// `epoll(7)` file descriptor, polling `signalfd` and `eventfd`. Also used
+    /// to identify the subscriber.
+    fd: RawFd,
+    /// Duplicate of `Hub.fd`.
+    signalfd: RawFd,
+    /// `eventfd(2)` file descriptor, written to when a signal is added to
+    /// `queue`.
+    eventfd: RawFd,
+    signals: SignalSet,
+    /// Signals received, but not yet returned to the user, see `push`.
+    queue: VecDeque<SignalInfo>,
+}
+
+/// Add a new subscriber for `signals`.
+///
+/// Returns the `epoll` file descriptor of the subscriber, which is closed by
+/// `unsubscribe`.
+pub(super) fn subscribe(signals: SignalSet) -> io::Result<RawFd> {
+    let mut hub = lock();
+    if hub.is_none() {
+        let set = create_sigset(signals)?;
+        let fd = new_signalfd(&set)?;
+        *hub = Some(Hub {
+            fd,
+            subscribers: Vec::new(),
+        });
+    }
+
+    let h = hub.as_mut().unwrap();
+    let result = h.subscribe(signals);
+    if h.subscribers.is_empty() {
+        // Failed to add the first subscriber.
+        *hub = None;
+    }
+    result
+}
+
+/// Remove the subscriber with `fd`, closing its file descriptors.
+pub(super) fn unsubscribe(fd: RawFd) {
+    let mut hub = lock();
+    if let Some(h) = hub.as_mut() {
+        // Closes the file descriptors of the subscriber.
+        h.subscribers.retain(|subscriber| subscriber.fd != fd);
+        if h.subscribers.is_empty() {
+            *hub = None;
+        } else if let Err(err) = h.update_mask() {
+            error!("error updating signal mask of shared signalfd: {}", err);
+        }
+    }
+}
+
+/// Receive a signal for the subscriber with `fd`.
+pub(super) fn receive(fd: RawFd) -> io::Result<Option<SignalInfo>> {
+    let mut hub = lock();
+    let hub = hub.as_mut().expect("missing shared signalfd");
+    hub.read(fd)?;
+    Ok(hub.subscriber(fd).queue.pop_front())
+}
+
+/// Receive multiple signals for the subscriber with `fd`.
+pub(super) fn receive_batch(fd: RawFd, infos: &mut [SignalInfo]) -> io::Result<usize> {
+    let mut hub = lock();
+    let hub = hub.as_mut().expect("missing shared signalfd");
+    hub.read(fd)?;
+    let queue = &mut hub.subscriber(fd).queue;
+    let n = min(infos.len(), queue.len());
+    for (info, queued) in infos.iter_mut().zip(queue.drain(..n)) {
+        *info = queued;
+    }
+    Ok(n)
+}
+
+fn lock() -> MutexGuard<'static, Option<Hub>> {
+    HUB.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Hub {
+    fn subscribe(&mut self, signals: SignalSet) -> io::Result<RawFd> {
+        let subscriber = Subscriber::new(self.fd, signals)?;
+        let fd = subscriber.fd;
+        self.subscribers.push(subscriber);
+        match self.update_mask() {
+            Ok(()) => Ok(fd),
+            Err(err) => {
+                // Closes the file descriptors.
+                let _ = self.subscribers.pop();
+                Err(err)
+            }
+        }
+    }
+
+    /// Update the signal mask of the `signalfd` to include the signals of all
+    /// subscribers.
+    fn update_mask(&mut self) -> io::Result<()> {
+        let signals = self
+            .subscribers
+            .iter()
+            .map(|subscriber| subscriber.signals)
+            .reduce(|a, b| a | b);
+        let signals = match signals {
+            Some(signals) => signals,
+            None => return Ok(()),
+        };
+        let set = create_sigset(signals)?;
+        if unsafe { libc::signalfd(self.fd, &set, 0) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn subscriber(&mut self, fd: RawFd) -> &mut Subscriber {
+        self.subscribers
+            .iter_mut()
+            .find(|subscriber| subscriber.fd == fd)
+            .expect("missing fan-out subscriber")
+    }
+
+    /// Read all signals from the `signalfd`, adding them to the queues of the
+    /// subscribers. All subscribers, except for `reader`, are notified.
+    fn read(&mut self, reader: RawFd) -> io::Result<()> {
+        // Reset our notification before reading, any signals added after this
+        // will notify us again.
+        reset_eventfd(self.subscriber(reader).eventfd)?;
+
+        let mut raw_infos = [MaybeUninit::uninit(); BATCH_SIZE];
+        loop {
+            let raw_infos = read_siginfos(self.fd, &mut raw_infos)?;
+            for info in raw_infos.iter().filter_map(signal_info) {
+                for subscriber in self.subscribers.iter_mut() {
+                    if !subscriber.signals.contains(info.signal) || !subscriber.push(info) {
+                        continue;
+                    }
+                    if subscriber.fd != reader {
+                        // The signal is already read from the `signalfd`, so
+                        // returning here would lose it for the others.
+                        if let Err(err) = notify_eventfd(subscriber.eventfd) {
+                            error!("error notifying fan-out Signals: {}", err);
+                        }
+                    }
+                }
+            }
+            if raw_infos.len() < BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Drop for Hub {
+    fn drop(&mut self) {
+        close(self.fd, "shared signalfd");
+    }
+}
+
+impl Subscriber {
+    /// Create a new subscriber for the shared `signalfd`.
+    fn new(signalfd: RawFd, signals: SignalSet) -> io::Result<Subscriber> {
+        let fd = match unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) } {
+            -1 => return Err(io::Error::last_os_error()),
+            fd => fd,
+        };
+        let mut subscriber = Subscriber {
+            fd,
+            signalfd: -1,
+            eventfd: -1,
+            signals,
+            queue: VecDeque::new(),
+        };
+
+        // NOTE: on error the file descriptors are closed when `subscriber` is
+        // dropped.
+        subscriber.signalfd = match unsafe { libc::fcntl(signalfd, libc::F_DUPFD_CLOEXEC, 0) } {
+            -1 => return Err(io::Error::last_os_error()),
+            fd => fd,
+        };
+        subscriber.eventfd = new_eventfd()?;
+        epoll_add(subscriber.fd, subscriber.signalfd)?;
+        epoll_add(subscriber.fd, subscriber.eventfd)?;
+        Ok(subscriber)
+    }
+
+    /// Add `info` to the queue. Like the kernel, standard signals that are
+    /// already queued are merged and at most `MAX_QUEUED` signals are queued.
+    /// Returns `false` if the signal isn't added.
+    fn push(&mut self, info: SignalInfo) -> bool {
+        let merge = !matches!(info.signal, Signal::Realtime(_))
+            && self.queue.iter().any(|queued| queued.signal == info.signal);
+        if merge || self.queue.len() >= MAX_QUEUED {
+            false
+        } else {
+            self.queue.push_back(info);
+            true
+        }
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        close(self.fd, "Signals");
+        if self.signalfd != -1 {
+            close(self.signalfd, "duplicate signalfd");
+        }
+        if self.eventfd != -1 {
+            close(self.eventfd, "Signals eventfd");
+        }
+    }
+}
+
+/// Add `fd` to the `epoll` instance `epfd`, polling for readable events.
+fn epoll_add(epfd: RawFd, fd: RawFd) -> io::Result<()> {
+    let mut event = libc::epoll_event {
+        events: libc::EPOLLIN as u32,
+        u64: fd as u64,
+    };
+    if unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
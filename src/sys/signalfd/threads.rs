@@ -0,0 +1,190 @@
+//! Blocking signals in all threads of the process, see `block_in_all_threads`.
+//!
+//! A thread can only change its own signal mask, so we ask every thread to
+//! block the signals by sending it a helper signal using `tgkill(2)`. The
+//! signal handler for the helper signal adds the signals to the signal mask
+//! that is restored once the handler returns (`ucontext_t.uc_sigmask`).
+
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use std::{fs, io, thread};
+
+use crate::sys::{
+    from_raw_signal, get_signal_action, raw_signal, set_signal_action, set_signal_handler,
+};
+use crate::{Signal, SignalSet};
+
+use super::REGISTRY;
+
+/// Serialises calls to `block_in_all_threads`, as the signal handler uses
+/// global state.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Raw signals the signal handler blocks, bit `n - 1` is set for raw signal
+/// `n`.
+static SIGNALS: AtomicU64 = AtomicU64::new(0);
+
+/// Thread id of the last thread that blocked the signals.
+static BLOCKED: AtomicI32 = AtomicI32::new(0);
+
+/// Maximum time to wait for a single thread to block the signals.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Block `signals` in all threads of the process, except the current thread.
+pub fn block_in_all_threads(signals: SignalSet) -> io::Result<()> {
+    let _lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let helper = helper_signal(signals)?;
+    let bits = signals
+        .into_iter()
+        .fold(0, |bits, signal| bits | 1 << (raw_signal(signal) - 1));
+    SIGNALS.store(bits, Ordering::SeqCst);
+
+    let helper = raw_signal(helper);
+    let old_action = set_signal_handler(helper, block_signals)?;
+    let res = block_threads(helper);
+    // Threads that didn't handle the helper signal, e.g. because they block
+    // it, still have it pending. Once unblocked the default action would
+    // terminate the process, so we discard them by ignoring the signal.
+    let res = discard_pending(helper).and(res);
+    set_signal_action(helper, &old_action).and(res)
+}
+
+/// Discard all pending instances of the raw signal `signal` by ignoring it.
+fn discard_pending(signal: libc::c_int) -> io::Result<()> {
+    // This is safe because all zeroes is a valid `sigaction`.
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = libc::SIG_IGN;
+    set_signal_action(signal, &action)
+}
+
+/// Returns the helper signal: the last real-time signal not in `signals` and
+/// not in use, i.e. not blocked by another `Signals` and without a signal
+/// handler.
+fn helper_signal(signals: SignalSet) -> io::Result<Signal> {
+    for raw in (libc::SIGRTMIN()..=libc::SIGRTMAX()).rev() {
+        let signal = match from_raw_signal(raw) {
+            Some(signal) if !signals.contains(signal) => signal,
+            _ => continue,
+        };
+        if !REGISTRY.contains(signal) && get_signal_action(raw)?.sa_sigaction == libc::SIG_DFL {
+            return Ok(signal);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "can't block signals in all threads when all real-time signals are in use",
+    ))
+}
+
+/// Send the helper signal to all threads, and wait for them to block the
+/// signals.
+fn block_threads(helper: libc::c_int) -> io::Result<()> {
+    let pid = unsafe { libc::getpid() };
+    let mut done = vec![gettid()];
+    // Threads can be spawned while we're blocking the signals, possibly by
+    // threads that didn't block the signals yet, so we keep going until we
+    // don't find any new threads.
+    loop {
+        let mut found_new = false;
+        for tid in threads()? {
+            if done.contains(&tid) {
+                continue;
+            }
+            found_new = true;
+            block_thread(pid, tid, helper)?;
+            done.push(tid);
+        }
+        if !found_new {
+            return Ok(());
+        }
+    }
+}
+
+/// Send the helper signal to thread `tid` and wait for it to block the
+/// signals.
+fn block_thread(pid: libc::pid_t, tid: libc::pid_t, helper: libc::c_int) -> io::Result<()> {
+    BLOCKED.store(0, Ordering::SeqCst);
+    if unsafe { libc::syscall(libc::SYS_tgkill, pid, tid, helper) } == -1 {
+        return match io::Error::last_os_error() {
+            // Thread already stopped.
+            ref err if err.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+            err => Err(err),
+        };
+    }
+
+    let deadline = Instant::now() + TIMEOUT;
+    while BLOCKED.load(Ordering::SeqCst) != tid {
+        if !Path::new(&format!("/proc/self/task/{}", tid)).exists() {
+            // Thread stopped before handling the signal.
+            return Ok(());
+        } else if Instant::now() >= deadline {
+            let msg = format!(
+                "thread {} didn't block the signals, it might block the helper signal",
+                tid
+            );
+            return Err(io::Error::new(io::ErrorKind::TimedOut, msg));
+        }
+        thread::sleep(Duration::from_micros(100));
+    }
+    Ok(())
+}
+
+/// Returns the ids of all threads in the process, using `/proc/self/task`.
+fn threads() -> io::Result<Vec<libc::pid_t>> {
+    let mut tids = Vec::new();
+    for entry in fs::read_dir("/proc/self/task")? {
+        if let Some(tid) = entry?.file_name().to_str().and_then(|n| n.parse().ok()) {
+            tids.push(tid);
+        }
+    }
+    Ok(tids)
+}
+
+/// Signal handler for the helper signal, see `block_in_all_threads`.
+///
+/// # Safety
+///
+/// This may only call async-signal-safe functions, see `signal-safety(7)`.
+extern "C" fn block_signals(_: libc::c_int, _: *mut libc::siginfo_t, context: *mut libc::c_void) {
+    let bits = SIGNALS.load(Ordering::SeqCst);
+    // The signal mask in `context` is restored once the handler returns, so
+    // adding the signals to it blocks them in the thread.
+    if let Some(context) = unsafe { context.cast::<libc::ucontext_t>().as_mut() } {
+        for signal in 1..=64 {
+            if bits & 1 << (signal - 1) != 0 {
+                let _ = unsafe { libc::sigaddset(&mut context.uc_sigmask, signal) };
+            }
+        }
+    }
+    BLOCKED.store(gettid(), Ordering::SeqCst);
+}
+
+/// Returns the thread id of the current thread.
+fn gettid() -> libc::pid_t {
+    unsafe { libc::syscall(libc::SYS_gettid) as libc::pid_t }
+}
+
+#[test]
+fn helper_signal_in_use() {
+    use super::{block_signals, create_sigset, unblock_signals};
+
+    let max = (libc::SIGRTMAX() - libc::SIGRTMIN()) as u8;
+    let last = Signal::Realtime(max);
+    assert_eq!(helper_signal(Signal::Interrupt.into()).unwrap(), last);
+    assert_eq!(
+        helper_signal(last.into()).unwrap(),
+        Signal::Realtime(max - 1)
+    );
+
+    // Signals blocked by another `Signals` can't be used.
+    let set = create_sigset(last.into()).unwrap();
+    block_signals(last.into(), &set).unwrap();
+    assert_eq!(
+        helper_signal(Signal::Interrupt.into()).unwrap(),
+        Signal::Realtime(max - 1)
+    );
+    unblock_signals(last.into()).unwrap();
+}
@@ -0,0 +1,245 @@
+//! Thread based implementation of `Signals`, see the `Signals` type.
+
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::mem::MaybeUninit;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::thread::JoinHandleExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::{fmt, io, ptr};
+
+use log::error;
+use mio::unix::SourceFd;
+use mio::{event, Interest, Registry, Token};
+
+use crate::{SignalInfo, SignalSet, UnblockedThread};
+
+use super::self_pipe::siginfo_signal_info;
+use super::signalfd::{
+    block_signals, close, create_sigset, forget_signals, new_eventfd, notify_eventfd,
+    reset_eventfd, sigprocmask, unblock_signals, unblocked_threads,
+};
+use super::{poll_readable, raw_signal};
+
+/// Signaler backed by a thread calling `sigwaitinfo(2)`.
+///
+/// # Implementation notes
+///
+/// We block the signals (like the signalfd implementation) and spawn a thread
+/// that waits for the signals using `sigwaitinfo(2)`. Received signals are
+/// added to `Shared::queue` after which the `eventfd(2)`, which is registered
+/// with `Poll`, is made readable.
+///
+/// To stop the thread we set `Shared::stop` and send the thread one of the
+/// signals it's waiting for, using `pthread_kill(3)`.
+pub struct Signals {
+    /// `eventfd(2)` file descriptor, readable if signals are added to the
+    /// queue.
+    fd: RawFd,
+    shared: Arc<Shared>,
+    /// Handle to the thread calling `sigwaitinfo(2)`, only `None` when
+    /// dropped.
+    handle: Option<JoinHandle<()>>,
+    /// All signals this is listening for, used in resetting the signal mask.
+    signals: SignalSet,
+    /// Whether or not to unblock the signals when dropped.
+    restore: bool,
+}
+
+/// State shared between `Signals` and its thread.
+struct Shared {
+    queue: Mutex<VecDeque<SignalInfo>>,
+    /// Whether or not the thread should stop.
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn queue(&self) -> MutexGuard<'_, VecDeque<SignalInfo>> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Signals {
+    pub fn new(signals: SignalSet) -> io::Result<Signals> {
+        let set = create_sigset(signals)?;
+        let fd = new_eventfd()?;
+        if let Err(err) = block_signals(signals, &set) {
+            close(fd, "Signals");
+            return Err(err);
+        }
+
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            stop: AtomicBool::new(false),
+        });
+        let thread_shared = shared.clone();
+        let spawned = thread::Builder::new()
+            .name("mio-signals".to_owned())
+            .spawn(move || wait_for_signals(set, fd, &thread_shared));
+        match spawned {
+            Ok(handle) => Ok(Signals {
+                fd,
+                shared,
+                handle: Some(handle),
+                signals,
+                restore: true,
+            }),
+            Err(err) => {
+                if let Err(err) = unblock_signals(signals) {
+                    error!("error unblocking signals: {}", err);
+                }
+                close(fd, "Signals");
+                Err(err)
+            }
+        }
+    }
+
+    pub fn receive_info(&mut self) -> io::Result<Option<SignalInfo>> {
+        if let Some(info) = self.shared.queue().pop_front() {
+            return Ok(Some(info));
+        }
+        // Reset the eventfd before checking the queue again, to not miss a
+        // notification for signals added in between.
+        reset_eventfd(self.fd)?;
+        Ok(self.shared.queue().pop_front())
+    }
+
+    pub fn receive_batch(&mut self, infos: &mut [SignalInfo]) -> io::Result<usize> {
+        let mut n = pop_batch(&mut self.shared.queue(), infos);
+        if n < infos.len() {
+            // See `receive_info`.
+            reset_eventfd(self.fd)?;
+            n += pop_batch(&mut self.shared.queue(), &mut infos[n..]);
+        }
+        Ok(n)
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        poll_readable(&[self.fd], timeout)
+    }
+
+    pub fn check_thread_masks(&self) -> io::Result<Vec<UnblockedThread>> {
+        unblocked_threads(self.signals)
+    }
+
+    pub fn set_restore(&mut self, restore: bool) {
+        self.restore = restore;
+    }
+}
+
+/// Pop as many signals from `queue` as fit into `infos`, returning the number
+/// of signals popped.
+fn pop_batch(queue: &mut VecDeque<SignalInfo>, infos: &mut [SignalInfo]) -> usize {
+    let n = min(queue.len(), infos.len());
+    for (info, popped) in infos.iter_mut().zip(queue.drain(..n)) {
+        *info = popped;
+    }
+    n
+}
+
+/// Function run by the thread, waiting for signals in `set` and adding them
+/// to the queue, making `fd` readable.
+fn wait_for_signals(set: libc::sigset_t, fd: RawFd, shared: &Shared) {
+    // `sigwaitinfo(2)` requires the signals to be blocked.
+    if let Err(err) = sigprocmask(libc::SIG_BLOCK, &set, ptr::null_mut()) {
+        error!("error blocking signals in Signals thread: {}", err);
+        return;
+    }
+
+    loop {
+        let mut info: MaybeUninit<libc::siginfo_t> = MaybeUninit::uninit();
+        if unsafe { libc::sigwaitinfo(&set, info.as_mut_ptr()) } == -1 {
+            match io::Error::last_os_error() {
+                ref err if err.kind() == io::ErrorKind::Interrupted => continue,
+                err => {
+                    error!("error waiting for signals: {}", err);
+                    return;
+                }
+            }
+        }
+
+        if shared.stop.load(Ordering::SeqCst) {
+            return;
+        }
+
+        // This is safe because `sigwaitinfo` initialised the info.
+        let info = unsafe { info.assume_init() };
+        if let Some(info) = siginfo_signal_info(&info) {
+            shared.queue().push_back(info);
+            if let Err(err) = notify_eventfd(fd) {
+                error!("error notifying Signals: {}", err);
+            }
+        }
+    }
+}
+
+impl event::Source for Signals {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.fd).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.fd).deregister(registry)
+    }
+}
+
+impl AsRawFd for Signals {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl fmt::Debug for Signals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signals").field("fd", &self.fd).finish()
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        // Stop the thread, waking it using one of the signals it's waiting for.
+        self.shared.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            // `SignalSet` is never empty.
+            let signal = self.signals.into_iter().next().unwrap();
+            let errno = unsafe { libc::pthread_kill(handle.as_pthread_t(), raw_signal(signal)) };
+            // If the thread already stopped (due to an error) this returns
+            // `ESRCH`, which is fine.
+            if errno != 0 && errno != libc::ESRCH {
+                let err = io::Error::from_raw_os_error(errno);
+                error!("error stopping Signals thread: {}", err);
+            } else if handle.join().is_err() {
+                error!("Signals thread panicked");
+            }
+        }
+
+        // Reverse the blocking of signals.
+        let res = if self.restore {
+            unblock_signals(self.signals)
+        } else {
+            forget_signals(self.signals)
+        };
+        if let Err(err) = res {
+            error!("error unblocking signals: {}", err);
+        }
+
+        close(self.fd, "Signals");
+    }
+}
@@ -0,0 +1,100 @@
+//! Module with [`Target`], [`Pid`] and [`Pgid`].
+
+use std::io;
+
+/// Target of [`send_signal_to`].
+///
+/// [`send_signal_to`]: crate::send_signal_to
+///
+/// # Notes
+///
+/// The variants map to the different meanings of the `pid` argument of
+/// [`kill(2)`].
+///
+/// [`kill(2)`]: https://man7.org/linux/man-pages/man2/kill.2.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Target {
+    /// A single process.
+    Process(Pid),
+    /// All processes in a process group.
+    ProcessGroup(Pgid),
+    /// All processes in the process group of the calling process.
+    OwnProcessGroup,
+    /// All processes the calling process has permission to send signals to,
+    /// except for `init` (process 1).
+    AllPermitted,
+}
+
+/// Process id.
+///
+/// This is guaranteed to be a valid process id, i.e. non-zero and fitting in
+/// `pid_t`.
+///
+/// # Examples
+///
+/// ```
+/// use mio_signals::Pid;
+///
+/// let pid = Pid::new(std::process::id()).unwrap();
+/// assert_eq!(pid.id(), std::process::id());
+///
+/// // `u32::MAX` doesn't fit in `pid_t`.
+/// assert!(Pid::new(u32::MAX).is_err());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Pid(u32);
+
+impl Pid {
+    /// Create a new process id.
+    ///
+    /// Returns an error (of kind [`io::ErrorKind::InvalidInput`]) if `pid` is
+    /// zero or doesn't fit in `pid_t`.
+    pub fn new(pid: u32) -> io::Result<Pid> {
+        if pid == 0 || pid > i32::MAX as u32 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid process id",
+            ))
+        } else {
+            Ok(Pid(pid))
+        }
+    }
+
+    /// Returns the process id.
+    pub const fn id(self) -> u32 {
+        self.0
+    }
+}
+
+/// Process group id.
+///
+/// This is guaranteed to be a valid process group id that can be used with
+/// [`kill(2)`], i.e. fitting in `pid_t` and larger than one (`-1` is used to
+/// signal all processes, see [`Target::AllPermitted`]).
+///
+/// [`kill(2)`]: https://man7.org/linux/man-pages/man2/kill.2.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Pgid(u32);
+
+impl Pgid {
+    /// Create a new process group id.
+    ///
+    /// Returns an error (of kind [`io::ErrorKind::InvalidInput`]) if `pgid` is
+    /// zero, one or doesn't fit in `pid_t`. To target the process group of the
+    /// calling process use [`Target::OwnProcessGroup`].
+    pub fn new(pgid: u32) -> io::Result<Pgid> {
+        if pgid <= 1 || pgid > i32::MAX as u32 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid process group id",
+            ))
+        } else {
+            Ok(Pgid(pgid))
+        }
+    }
+
+    /// Returns the process group id.
+    pub const fn id(self) -> u32 {
+        self.0
+    }
+}
@@ -0,0 +1,138 @@
+//! Module with [`AsyncSignals`].
+
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::unix::AsyncFd;
+
+use crate::{Signal, SignalInfo, SignalSet, Signals};
+
+/// Asynchronous notification of process signals, using Tokio.
+///
+/// This wraps [`Signals`], using the same file descriptor and receive path,
+/// but is driven by Tokio's reactor (using [`AsyncFd`]) instead of
+/// [`mio::Poll`]. Signals can be received using [`AsyncSignals::recv`] or by
+/// using it as [`Stream`].
+///
+/// The same requirements for multithreaded processes apply as for
+/// [`Signals`], see its documentation. Note that this means that the
+/// `AsyncSignals` should be created before starting a multithreaded Tokio
+/// runtime, it can be converted into an `AsyncSignals` using
+/// [`AsyncSignals::from_signals`] once the runtime is started.
+///
+/// This type is only available if the `tokio` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use std::{io, process};
+///
+/// use mio_signals::{send_signal, AsyncSignals, Signal, Signals};
+///
+/// fn main() -> io::Result<()> {
+///     // Create `Signals` before starting the runtime (and its threads).
+///     let signals = Signals::new(Signal::Interrupt.into())?;
+///
+///     let runtime = tokio::runtime::Builder::new_current_thread()
+///         .enable_io()
+///         .build()?;
+///     runtime.block_on(async move {
+///         let mut signals = AsyncSignals::from_signals(signals)?;
+///         # // Don't want to wait for ever.
+///         # send_signal(process::id(), Signal::Interrupt)?;
+///
+///         let signal = signals.recv().await?;
+///         assert_eq!(signal, Signal::Interrupt);
+///         Ok(())
+///     })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsyncSignals {
+    inner: AsyncFd<Signals>,
+}
+
+impl AsyncSignals {
+    /// Create a new asynchronous signal notifier.
+    ///
+    /// See [`Signals::new`].
+    ///
+    /// # Panics
+    ///
+    /// This must be called within the context of a Tokio runtime, with I/O
+    /// enabled, see [`AsyncFd::new`].
+    pub fn new(signals: SignalSet) -> io::Result<AsyncSignals> {
+        Signals::new(signals).and_then(AsyncSignals::from_signals)
+    }
+
+    /// Create a new asynchronous signal notifier from `signals`.
+    ///
+    /// # Panics
+    ///
+    /// This must be called within the context of a Tokio runtime, with I/O
+    /// enabled, see [`AsyncFd::new`].
+    pub fn from_signals(signals: Signals) -> io::Result<AsyncSignals> {
+        AsyncFd::new(signals).map(|inner| AsyncSignals { inner })
+    }
+
+    /// Receive a signal.
+    pub async fn recv(&mut self) -> io::Result<Signal> {
+        self.recv_info().await.map(|info| info.signal())
+    }
+
+    /// Receive a signal, including information about it.
+    ///
+    /// See [`SignalInfo`] for the information that is available.
+    pub async fn recv_info(&mut self) -> io::Result<SignalInfo> {
+        poll_fn(|ctx| self.poll_recv_info(ctx)).await
+    }
+
+    /// Attempt to receive a signal, including information about it.
+    ///
+    /// If no signal is available this returns `Poll::Pending` and arranges for
+    /// the current task to be woken once a signal is available.
+    pub fn poll_recv_info(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<SignalInfo>> {
+        loop {
+            let mut guard = ready!(self.inner.poll_read_ready_mut(ctx))?;
+            let result = guard.try_io(|inner| match inner.get_mut().receive_info() {
+                Ok(Some(info)) => Ok(info),
+                Ok(None) => Err(io::ErrorKind::WouldBlock.into()),
+                Err(err) => Err(err),
+            });
+            match result {
+                Ok(result) => return Poll::Ready(result),
+                // No signals available, readiness is cleared by `try_io`.
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Returns a reference to the underlying [`Signals`].
+    pub fn get_ref(&self) -> &Signals {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying [`Signals`].
+    pub fn get_mut(&mut self) -> &mut Signals {
+        self.inner.get_mut()
+    }
+
+    /// Returns the underlying [`Signals`], deregistering it from the Tokio
+    /// reactor.
+    pub fn into_inner(self) -> Signals {
+        self.inner.into_inner()
+    }
+}
+
+impl Stream for AsyncSignals {
+    type Item = io::Result<Signal>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_recv_info(ctx)
+            .map(|result| Some(result.map(|info| info.signal())))
+    }
+}
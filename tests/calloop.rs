@@ -0,0 +1,35 @@
+//! Tests for `SignalSource`.
+
+#![cfg(feature = "calloop")]
+
+use std::time::Duration;
+
+use calloop::EventLoop;
+use mio_signals::{Signal, SignalInfo, SignalSource};
+
+#[test]
+fn signal_source() {
+    let mut event_loop: EventLoop<Vec<SignalInfo>> =
+        EventLoop::try_new().expect("unable to create EventLoop");
+    let signals =
+        SignalSource::new(Signal::User1 | Signal::User2).expect("unable to create SignalSource");
+    let _ = event_loop
+        .handle()
+        .insert_source(signals, |info, _, received| received.push(info))
+        .expect("unable to insert SignalSource");
+
+    // Send the signals to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        event_loop
+            .dispatch(Some(Duration::from_secs(1)), &mut received)
+            .expect("unable to dispatch events");
+    }
+    let mut signals: Vec<Signal> = received.iter().map(|info| info.signal()).collect();
+    signals.sort_unstable();
+    assert_eq!(signals, vec![Signal::User1, Signal::User2]);
+}
@@ -0,0 +1,93 @@
+//! Tests for `ChildWatcher`.
+//!
+//! # Notes
+//!
+//! `ChildWatcher` reaps all child processes and it must be created on the main
+//! thread, so this needs to run on its own without the test harness.
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{send_signal, ChildWatcher, Signal};
+
+const CHILD: Token = Token(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let start = Instant::now();
+    println!("\nrunning 1 test");
+
+    child_watcher();
+
+    println!("test child_watcher ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+}
+
+fn child_watcher() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    let mut children = ChildWatcher::new().unwrap();
+    poll.registry()
+        .register(&mut children, CHILD, Interest::READABLE)
+        .unwrap();
+    assert!(children.receive().unwrap().is_none());
+
+    // Child process that exits by itself.
+    #[allow(clippy::zombie_processes)] // Reaped by `ChildWatcher`.
+    let child = Command::new("true").stdin(Stdio::null()).spawn().unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut children, child.id());
+    assert!(status.success());
+
+    // Child process that is stopped, continued and killed.
+    #[allow(clippy::zombie_processes)] // Reaped by `ChildWatcher`.
+    let child = Command::new("sleep")
+        .arg("10")
+        .stdin(Stdio::null())
+        .spawn()
+        .unwrap();
+    let pid = child.id();
+
+    stop(pid);
+    let status = wait_for(&mut poll, &mut events, &mut children, pid);
+    assert_eq!(status.stopped_signal(), Some(libc::SIGSTOP));
+
+    cont(pid);
+    let status = wait_for(&mut poll, &mut events, &mut children, pid);
+    assert!(status.continued());
+
+    send_signal(pid, Signal::Terminate).unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut children, pid);
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+
+    // The child process is reaped, no more state changes.
+    assert!(children.receive().unwrap().is_none());
+}
+
+/// Wait for a state change of the process with `pid`.
+fn wait_for(
+    poll: &mut Poll,
+    events: &mut Events,
+    children: &mut ChildWatcher,
+    pid: u32,
+) -> ExitStatus {
+    loop {
+        poll.poll(events, Some(TIMEOUT)).unwrap();
+        assert!(!events.is_empty(), "timed out waiting for child process");
+
+        if let Some((got_pid, status)) = children.receive().unwrap() {
+            assert_eq!(got_pid, pid);
+            return status;
+        }
+    }
+}
+
+fn stop(pid: u32) {
+    assert_eq!(unsafe { libc::kill(pid as libc::pid_t, libc::SIGSTOP) }, 0);
+}
+
+fn cont(pid: u32) {
+    assert_eq!(unsafe { libc::kill(pid as libc::pid_t, libc::SIGCONT) }, 0);
+}
@@ -4,8 +4,18 @@
 //!
 //! This needs to run on its own and thus has its own file.
 
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
 use mio_signals::Signal;
 
+/// The signal mask and actions are shared between all `Signals`, so the tests
+/// can't run concurrently.
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 #[cfg(any(
     target_os = "dragonfly",
     target_os = "freebsd",
@@ -18,14 +28,16 @@ mod kqueue {
     use std::mem::MaybeUninit;
     use std::{io, ptr};
 
-    use mio_signals::{SignalSet, Signals};
+    use mio_signals::{Signal, SignalSet, Signals};
 
-    use super::raw_signal;
+    use super::{lock, raw_signal};
 
     type SigActions = [MaybeUninit<libc::sigaction>; SignalSet::all().len()];
 
     #[test]
     fn cleanup() {
+        let _lock = lock();
+
         // Before `Signals` is created.
         let mut original_actions: SigActions = unsafe { MaybeUninit::uninit().assume_init() };
         let original_actions = get_sigactions(&mut original_actions).unwrap();
@@ -63,6 +75,41 @@ mod kqueue {
         }
     }
 
+    #[test]
+    fn cleanup_overlapping_signals() {
+        let _lock = lock();
+
+        let signals1 = Signals::new(SignalSet::all()).unwrap();
+        let signals2 = Signals::new(Signal::Interrupt | Signal::Terminate).unwrap();
+
+        // After the first `Signals` is dropped the signals used by the second
+        // `Signals` should remain ignored.
+        drop(signals1);
+        let mut actions: SigActions = unsafe { MaybeUninit::uninit().assume_init() };
+        let actions = get_sigactions(&mut actions).unwrap();
+        for (signal, action) in SignalSet::all().into_iter().zip(actions) {
+            let want_ignored = signal == Signal::Interrupt || signal == Signal::Terminate;
+            assert_eq!(
+                action.sa_sigaction == libc::SIG_IGN,
+                want_ignored,
+                "incorrect signal action for signal {:?}",
+                signal
+            );
+        }
+
+        drop(signals2);
+        let mut actions: SigActions = unsafe { MaybeUninit::uninit().assume_init() };
+        let actions = get_sigactions(&mut actions).unwrap();
+        for (signal, action) in SignalSet::all().into_iter().zip(actions) {
+            assert_ne!(
+                action.sa_sigaction,
+                libc::SIG_IGN,
+                "signal {:?} is still ignored",
+                signal
+            );
+        }
+    }
+
     fn get_sigactions(actions: &mut SigActions) -> io::Result<&[libc::sigaction]> {
         for (signal, old_action) in SignalSet::all().into_iter().zip(actions.iter_mut()) {
             if unsafe {
@@ -83,10 +130,12 @@ mod signalfd {
 
     use mio_signals::{Signal, SignalSet, Signals};
 
-    use super::raw_signal;
+    use super::{lock, raw_signal};
 
     #[test]
     fn cleanup() {
+        let _lock = lock();
+
         // Before `Signals` is created.
         let original_set = get_blocked_set().unwrap();
         for signal in SignalSet::all() {
@@ -113,6 +162,107 @@ mod signalfd {
         }
     }
 
+    #[test]
+    fn cleanup_keeps_blocked_signals() {
+        let _lock = lock();
+
+        // Block a signal before `Signals` is created.
+        let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+        unsafe {
+            assert_eq!(libc::sigemptyset(set.as_mut_ptr()), 0);
+            assert_eq!(libc::sigaddset(set.as_mut_ptr(), libc::SIGUSR1), 0);
+            assert_eq!(
+                libc::pthread_sigmask(libc::SIG_BLOCK, set.as_ptr(), ptr::null_mut()),
+                0
+            );
+        }
+        let original_set = get_blocked_set().unwrap();
+        assert!(is_in_set(&original_set, Signal::User1));
+
+        let signals = Signals::new(SignalSet::all()).unwrap();
+        drop(signals);
+
+        // After `Signals` is dropped only the signals that weren't blocked
+        // before should be unblocked.
+        let cleaned_set = get_blocked_set().unwrap();
+        for signal in SignalSet::all() {
+            assert_eq!(
+                is_in_set(&cleaned_set, signal),
+                signal == Signal::User1,
+                "incorrect blocked state for signal {:?}",
+                signal
+            );
+        }
+
+        unsafe {
+            assert_eq!(
+                libc::pthread_sigmask(libc::SIG_UNBLOCK, set.as_ptr(), ptr::null_mut()),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn cleanup_overlapping_signals() {
+        let _lock = lock();
+
+        let signals1 = Signals::new(SignalSet::all()).unwrap();
+        let signals2 = Signals::new(Signal::Interrupt | Signal::Terminate).unwrap();
+
+        // After the first `Signals` is dropped the signals used by the second
+        // `Signals` should remain blocked.
+        drop(signals1);
+        let blocked_set = get_blocked_set().unwrap();
+        for signal in SignalSet::all() {
+            assert_eq!(
+                is_in_set(&blocked_set, signal),
+                signal == Signal::Interrupt || signal == Signal::Terminate,
+                "incorrect blocked state for signal {:?}",
+                signal
+            );
+        }
+
+        drop(signals2);
+        let cleaned_set = get_blocked_set().unwrap();
+        for signal in SignalSet::all() {
+            assert!(!is_in_set(&cleaned_set, signal));
+        }
+    }
+
+    #[test]
+    fn no_restore_on_drop() {
+        let _lock = lock();
+
+        let signals = Signals::builder(Signal::User1 | Signal::User2)
+            .restore_on_drop(false)
+            .build()
+            .unwrap();
+        drop(signals);
+
+        // The signals should remain blocked after `Signals` is dropped.
+        let blocked_set = get_blocked_set().unwrap();
+        assert!(is_in_set(&blocked_set, Signal::User1));
+        assert!(is_in_set(&blocked_set, Signal::User2));
+
+        let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+        unsafe {
+            assert_eq!(libc::sigemptyset(set.as_mut_ptr()), 0);
+            assert_eq!(libc::sigaddset(set.as_mut_ptr(), libc::SIGUSR1), 0);
+            assert_eq!(libc::sigaddset(set.as_mut_ptr(), libc::SIGUSR2), 0);
+            assert_eq!(
+                libc::pthread_sigmask(libc::SIG_UNBLOCK, set.as_ptr(), ptr::null_mut()),
+                0
+            );
+        }
+
+        // A `Signals` created afterwards should still clean up the signals.
+        let signals = Signals::new(Signal::User1 | Signal::User2).unwrap();
+        drop(signals);
+        let cleaned_set = get_blocked_set().unwrap();
+        assert!(!is_in_set(&cleaned_set, Signal::User1));
+        assert!(!is_in_set(&cleaned_set, Signal::User2));
+    }
+
     fn get_blocked_set() -> io::Result<libc::sigset_t> {
         let mut old_set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
         if unsafe { libc::sigprocmask(0, ptr::null_mut(), old_set.as_mut_ptr()) } == -1 {
@@ -133,6 +283,85 @@ mod signalfd {
     }
 }
 
+mod self_pipe {
+    use std::io;
+    use std::mem::MaybeUninit;
+    use std::ptr;
+
+    use mio_signals::{Signal, SignalSet, Signals};
+
+    use super::{lock, raw_signal};
+
+    #[test]
+    fn cleanup() {
+        let _lock = lock();
+
+        // Before `Signals` is created.
+        let original_handlers = get_handlers().unwrap();
+
+        // After `Signals` is created.
+        let signals = Signals::new_self_pipe(SignalSet::all()).unwrap();
+        for (signal, handler) in SignalSet::all().into_iter().zip(get_handlers().unwrap()) {
+            assert!(
+                handler != libc::SIG_DFL && handler != libc::SIG_IGN,
+                "no signal handler installed for signal {:?}",
+                signal
+            );
+        }
+
+        // After `Signals` is dropped.
+        drop(signals);
+        assert_eq!(get_handlers().unwrap(), original_handlers);
+    }
+
+    #[test]
+    fn cleanup_overlapping_signals() {
+        let _lock = lock();
+
+        let original_handlers = get_handlers().unwrap();
+        let signals1 = Signals::new_self_pipe(SignalSet::all()).unwrap();
+        let signals2 = Signals::new_self_pipe(Signal::Interrupt | Signal::Terminate).unwrap();
+
+        // After the first `Signals` is dropped the signals used by the second
+        // `Signals` should still have a signal handler.
+        drop(signals1);
+        let handlers = get_handlers().unwrap();
+        for ((signal, handler), original) in SignalSet::all()
+            .into_iter()
+            .zip(handlers)
+            .zip(&original_handlers)
+        {
+            let want_handler = signal == Signal::Interrupt || signal == Signal::Terminate;
+            assert_eq!(
+                handler != *original,
+                want_handler,
+                "incorrect signal handler for signal {:?}",
+                signal
+            );
+        }
+
+        drop(signals2);
+        assert_eq!(get_handlers().unwrap(), original_handlers);
+    }
+
+    fn get_handlers() -> io::Result<Vec<libc::sighandler_t>> {
+        SignalSet::all()
+            .into_iter()
+            .map(|signal| {
+                let mut action: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+                if unsafe { libc::sigaction(raw_signal(signal), ptr::null(), action.as_mut_ptr()) }
+                    == -1
+                {
+                    Err(io::Error::last_os_error())
+                } else {
+                    // This is safe because `sigaction` initialised the action.
+                    Ok(unsafe { action.assume_init() }.sa_sigaction)
+                }
+            })
+            .collect()
+    }
+}
+
 // Keep in sync with `mio_signals::sys::raw_signal`.
 fn raw_signal(signal: Signal) -> libc::c_int {
     match signal {
@@ -141,5 +370,11 @@ fn raw_signal(signal: Signal) -> libc::c_int {
         Signal::Terminate => libc::SIGTERM,
         Signal::User1 => libc::SIGUSR1,
         Signal::User2 => libc::SIGUSR2,
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Child => libc::SIGCHLD,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Signal::Realtime(n) => libc::SIGRTMIN() + libc::c_int::from(n),
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        Signal::Realtime(_) => unreachable!("real-time signals are not supported"),
     }
 }
@@ -0,0 +1,60 @@
+//! Tests for `CommandExt`.
+
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use std::os::unix::process::CommandExt as _;
+use std::process::Command;
+use std::{io, ptr};
+
+use mio_signals::{CommandExt, Signal, Signals};
+
+const EMPTY_MASK: &str = "SigBlk:\t0000000000000000";
+
+#[test]
+fn reset_signal_mask() {
+    let signals =
+        Signals::new(Signal::Interrupt | Signal::Terminate).expect("unable to create Signals");
+
+    let mut cmd = Command::new("/bin/sh");
+    cmd.args(["-c", "grep SigBlk /proc/self/status"])
+        .reset_signal_mask();
+    assert_eq!(blocked_signals(&mut cmd), EMPTY_MASK);
+
+    drop(signals);
+}
+
+#[test]
+fn reset_signal_mask_after_pre_exec() {
+    // The standard library resets the signal mask before calling the
+    // `pre_exec` closures, so we block the signals in a closure to make sure
+    // the child process would inherit them.
+    let mut cmd = Command::new("grep");
+    cmd.args(["SigBlk", "/proc/self/status"]);
+    // This is safe because `block_signals` is async-signal-safe.
+    unsafe { cmd.pre_exec(block_signals) };
+    assert_ne!(blocked_signals(&mut cmd), EMPTY_MASK);
+
+    cmd.reset_signal_mask();
+    assert_eq!(blocked_signals(&mut cmd), EMPTY_MASK);
+}
+
+/// Block `SIGINT` and `SIGTERM` in the current thread.
+fn block_signals() -> io::Result<()> {
+    unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGINT);
+        libc::sigaddset(&mut set, libc::SIGTERM);
+        match libc::pthread_sigmask(libc::SIG_BLOCK, &set, ptr::null_mut()) {
+            0 => Ok(()),
+            errno => Err(io::Error::from_raw_os_error(errno)),
+        }
+    }
+}
+
+/// Returns the `SigBlk` line of `/proc/self/status` printed by `cmd`.
+fn blocked_signals(cmd: &mut Command) -> String {
+    let output = cmd.output().expect("unable to spawn child process");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
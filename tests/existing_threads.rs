@@ -0,0 +1,152 @@
+//! Tests `Signals::new_all_threads` in a process that already spawned threads.
+//!
+//! # Notes
+//!
+//! This needs to run on its own and thus has its own file, without the test
+//! harness as it spawns threads.
+
+use std::mem::MaybeUninit;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+use std::{io, process, ptr, thread};
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{send_signal, Signal, SignalSet, Signals};
+
+const SIGNAL: Token = Token(10);
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+type Test = fn() -> io::Result<()>;
+
+fn main() -> io::Result<()> {
+    let start = Instant::now();
+    let tests: &[(&str, Test)] = &[
+        ("existing_threads", existing_threads),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("blocked_helper_signal", blocked_helper_signal),
+    ];
+    println!("\nrunning {} tests", tests.len());
+
+    for (name, test) in tests.iter() {
+        test()?;
+        println!("test {} ... ok", name);
+    }
+
+    println!("\ntest result: ok. {} passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", tests.len(), start.elapsed());
+    Ok(())
+}
+
+fn existing_threads() -> io::Result<()> {
+    // Spawn the threads *before* creating `Signals`, they don't block any
+    // signals.
+    let handles = (0..5)
+        .map(|_| {
+            let (ready_sender, ready_receiver) = channel();
+            let (sender, receiver) = channel();
+            let handle = thread::spawn(move || {
+                unblock_all_signals();
+                ready_sender.send(()).unwrap();
+                wait_for_msg(receiver)
+            });
+            ready_receiver.recv().unwrap();
+            (sender, handle)
+        })
+        .collect::<Vec<_>>();
+
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(8);
+
+    let set = SignalSet::all();
+    let mut signals = Signals::new_all_threads(set)?;
+    poll.registry()
+        .register(&mut signals, SIGNAL, Interest::READABLE)?;
+
+    // If any thread didn't block the signals this would stop the process.
+    let want = vec![Signal::Interrupt, Signal::User1, Signal::Terminate];
+    for signal in want.iter().copied() {
+        send_signal(process::id(), signal)?;
+    }
+
+    let mut got = Vec::new();
+    while got.len() < want.len() {
+        poll.poll(&mut events, Some(TIMEOUT))?;
+        if events.is_empty() {
+            panic!("failed to get signal event, got signals: {:?}", got);
+        }
+
+        for event in events.iter() {
+            match event.token() {
+                SIGNAL => {
+                    while let Some(signal) = signals.receive()? {
+                        got.push(signal);
+                    }
+                }
+                _ => println!("Got unknown event: {:?}", event),
+            }
+        }
+    }
+    got.sort_by_key(|signal| want.iter().position(|s| s == signal));
+    assert_eq!(got, want);
+
+    for (sender, handle) in handles {
+        sender.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    Ok(())
+}
+
+/// A thread blocking the helper signal can't block the signals, but once it
+/// unblocks the helper signal it shouldn't terminate the process.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn blocked_helper_signal() -> io::Result<()> {
+    let (ready_sender, ready_receiver) = channel();
+    let (sender, receiver) = channel();
+    let handle = thread::spawn(move || {
+        block_all_signals();
+        ready_sender.send(()).unwrap();
+        wait_for_msg(receiver);
+        // If the helper signal is still pending this would terminate the
+        // process.
+        unblock_all_signals();
+        thread::sleep(Duration::from_millis(10));
+    });
+    ready_receiver.recv().unwrap();
+
+    let err = Signals::new_all_threads(Signal::User2.into()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    sender.send(()).unwrap();
+    handle.join().unwrap();
+    Ok(())
+}
+
+/// Block all signals in the current thread.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn block_all_signals() {
+    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    unsafe {
+        assert_eq!(libc::sigfillset(set.as_mut_ptr()), 0);
+        assert_eq!(
+            libc::pthread_sigmask(libc::SIG_SETMASK, set.as_ptr(), ptr::null_mut()),
+            0
+        );
+    }
+}
+
+/// Unblock all signals in the current thread, like a thread spawned by e.g. a C
+/// library.
+fn unblock_all_signals() {
+    let mut set: MaybeUninit<libc::sigset_t> = MaybeUninit::uninit();
+    unsafe {
+        assert_eq!(libc::sigemptyset(set.as_mut_ptr()), 0);
+        assert_eq!(
+            libc::pthread_sigmask(libc::SIG_SETMASK, set.as_ptr(), ptr::null_mut()),
+            0
+        );
+    }
+}
+
+fn wait_for_msg(receiver: Receiver<()>) {
+    receiver.recv().unwrap();
+}
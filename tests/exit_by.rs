@@ -0,0 +1,54 @@
+//! Tests for `Signal::exit_by`.
+//!
+//! # Notes
+//!
+//! `Signal::exit_by` exits the process, so this runs itself as a child process
+//! (with `EXIT_BY` set) and checks how it exited.
+
+use std::env;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus};
+use std::time::Instant;
+
+use mio_signals::{Signal, Signals};
+
+/// Environment variable set for the child process.
+const EXIT_BY: &str = "EXIT_BY";
+
+fn main() {
+    if let Ok(signal) = env::var(EXIT_BY) {
+        let signal = match &*signal {
+            "terminate" => Signal::Terminate,
+            "interrupt" => Signal::Interrupt,
+            "child" => Signal::Child,
+            _ => unreachable!(),
+        };
+        // `exit_by` must undo the blocking of the signals by `Signals`.
+        let _signals = Signals::new(Signal::Terminate | Signal::Interrupt | Signal::Child).unwrap();
+        signal.exit_by();
+    }
+
+    let start = Instant::now();
+    println!("\nrunning 2 tests");
+
+    let status = run("terminate");
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+    let status = run("interrupt");
+    assert_eq!(status.signal(), Some(libc::SIGINT));
+    println!("test exit_by_signal ... ok");
+
+    // Default action of `SIGCHLD` is to ignore it.
+    let status = run("child");
+    assert_eq!(status.code(), Some(128 + libc::SIGCHLD));
+    println!("test exit_by_fallback ... ok\n");
+
+    println!("test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+}
+
+/// Run this test as child process, exiting by `signal`.
+fn run(signal: &str) -> ExitStatus {
+    Command::new(env::current_exe().unwrap())
+        .env(EXIT_BY, signal)
+        .status()
+        .unwrap()
+}
@@ -0,0 +1,111 @@
+//! Tests for `SignalForwarder`.
+//!
+//! # Notes
+//!
+//! The signals are sent to the process, so this needs to run on its own
+//! without the test harness (which runs tests in different threads).
+
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{send_signal, Pgid, Signal, SignalForwarder};
+
+const FORWARDER: Token = Token(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let start = Instant::now();
+    println!("\nrunning 3 tests");
+
+    forward_to_child();
+    println!("test forward_to_child ... ok");
+    forward_to_process_group();
+    println!("test forward_to_process_group ... ok");
+    child_exits();
+    println!("test child_exits ... ok\n");
+
+    println!("test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+}
+
+fn forward_to_child() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    #[allow(clippy::zombie_processes)] // Reaped by `SignalForwarder`.
+    let child = Command::new("sleep")
+        .arg("10")
+        .stdin(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut forwarder =
+        SignalForwarder::new(&child, Signal::Terminate | Signal::Interrupt).unwrap();
+    poll.registry()
+        .register(&mut forwarder, FORWARDER, Interest::READABLE)
+        .unwrap();
+    assert!(forwarder.forward().unwrap().is_none());
+
+    send_signal(process::id(), Signal::Terminate).unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut forwarder);
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+
+    // Keeps returning the exit status.
+    assert_eq!(forwarder.forward().unwrap(), Some(status));
+    assert_eq!(forwarder.exit_status(), Some(status));
+}
+
+fn forward_to_process_group() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    #[allow(clippy::zombie_processes)] // Reaped by `SignalForwarder`.
+    let child = Command::new("sleep")
+        .arg("10")
+        .stdin(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .unwrap();
+    let pgid = Pgid::new(child.id()).unwrap();
+    let mut forwarder = SignalForwarder::process_group(pgid, Signal::User1.into()).unwrap();
+    poll.registry()
+        .register(&mut forwarder, FORWARDER, Interest::READABLE)
+        .unwrap();
+
+    send_signal(process::id(), Signal::User1).unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut forwarder);
+    assert_eq!(status.signal(), Some(libc::SIGUSR1));
+}
+
+fn child_exits() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    #[allow(clippy::zombie_processes)] // Reaped by `SignalForwarder`.
+    let child = Command::new("sh")
+        .args(["-c", "exit 3"])
+        .stdin(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut forwarder = SignalForwarder::new(&child, Signal::Terminate.into()).unwrap();
+    poll.registry()
+        .register(&mut forwarder, FORWARDER, Interest::READABLE)
+        .unwrap();
+
+    let status = wait_for(&mut poll, &mut events, &mut forwarder);
+    assert_eq!(status.code(), Some(3));
+}
+
+/// Wait for the child process of `forwarder` to exit.
+fn wait_for(poll: &mut Poll, events: &mut Events, forwarder: &mut SignalForwarder) -> ExitStatus {
+    loop {
+        // The child process could have exited before `SignalForwarder` was
+        // created, in which case we won't get an event.
+        if let Some(status) = forwarder.forward().unwrap() {
+            return status;
+        }
+
+        poll.poll(events, Some(TIMEOUT)).unwrap();
+        assert!(!events.is_empty(), "timed out waiting for child process");
+    }
+}
@@ -0,0 +1,58 @@
+//! Tests for `SignalStream`.
+//!
+//! # Notes
+//!
+//! The async-io reactor polls from its own thread, so the signals must be
+//! blocked in all threads. This means the `SignalStream` must be created on
+//! the main thread, before any other threads are spawned, so this needs to run
+//! on its own without the test harness.
+
+#[cfg(feature = "futures")]
+fn main() {
+    let start = std::time::Instant::now();
+    println!("\nrunning 1 test");
+
+    signal_stream::signal_stream();
+
+    println!("test signal_stream ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+}
+
+#[cfg(not(feature = "futures"))]
+fn main() {}
+
+#[cfg(feature = "futures")]
+mod signal_stream {
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use std::process;
+    use std::thread::{self, sleep};
+    use std::time::Duration;
+
+    use futures_core::Stream;
+    use mio_signals::{send_signal, Signal, SignalStream};
+
+    pub fn signal_stream() {
+        let mut signals = SignalStream::new(Signal::User1 | Signal::User2)
+            .expect("unable to create SignalStream");
+
+        // Send the signals once we're waiting on them.
+        let handle = thread::spawn(|| {
+            sleep(Duration::from_millis(50));
+            send_signal(process::id(), Signal::User1).unwrap();
+            sleep(Duration::from_millis(50));
+            send_signal(process::id(), Signal::User2).unwrap();
+        });
+
+        async_io::block_on(async {
+            for want in [Signal::User1, Signal::User2] {
+                let got = poll_fn(|ctx| Pin::new(&mut signals).poll_next(ctx))
+                    .await
+                    .expect("stream ended")
+                    .expect("unable to receive signal");
+                assert_eq!(got, want);
+            }
+        });
+        handle.join().unwrap();
+    }
+}
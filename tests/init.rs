@@ -0,0 +1,151 @@
+//! Tests for `InitSupervisor`.
+//!
+//! # Notes
+//!
+//! `InitSupervisor` reaps all child processes and the signals are sent to the
+//! process, so this needs to run on its own without the test harness.
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{send_signal, InitSupervisor, Signal};
+
+const SUPERVISOR: Token = Token(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let start = Instant::now();
+    let tests: &[(&str, fn())] = &[
+        ("forward_signal", forward_signal),
+        ("ignored_hangup", ignored_hangup),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("reap_adopted_process", reap_adopted_process),
+    ];
+    println!("\nrunning {} tests", tests.len());
+
+    for (name, test) in tests.iter() {
+        test();
+        println!("test {} ... ok", name);
+    }
+
+    println!("\ntest result: ok. {} passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", tests.len(), start.elapsed());
+}
+
+fn forward_signal() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    #[allow(clippy::zombie_processes)] // Reaped by `InitSupervisor`.
+    let child = Command::new("sleep")
+        .arg("10")
+        .stdin(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut supervisor = InitSupervisor::new(&child).unwrap();
+    poll.registry()
+        .register(&mut supervisor, SUPERVISOR, Interest::READABLE)
+        .unwrap();
+    assert!(supervisor.receive().unwrap().is_none());
+    assert_eq!(supervisor.exit_code(), None);
+
+    send_signal(process::id(), Signal::Terminate).unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut supervisor);
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+    assert_eq!(supervisor.exit_code(), Some(128 + libc::SIGTERM));
+}
+
+/// `SIGHUP` is ignored when started using `nohup(1)`, it should still be
+/// forwarded.
+fn ignored_hangup() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    // Spawn the child before ignoring `SIGHUP`, otherwise it would inherit it.
+    #[allow(clippy::zombie_processes)] // Reaped by `InitSupervisor`.
+    let child = Command::new("sleep")
+        .arg("10")
+        .stdin(Stdio::null())
+        .spawn()
+        .unwrap();
+    assert_ne!(
+        unsafe { libc::signal(libc::SIGHUP, libc::SIG_IGN) },
+        libc::SIG_ERR
+    );
+
+    let mut supervisor = InitSupervisor::new(&child).unwrap();
+    poll.registry()
+        .register(&mut supervisor, SUPERVISOR, Interest::READABLE)
+        .unwrap();
+
+    send_signal(process::id(), Signal::Hangup).unwrap();
+    let status = wait_for(&mut poll, &mut events, &mut supervisor);
+    assert_eq!(status.signal(), Some(libc::SIGHUP));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn reap_adopted_process() {
+    use std::fs;
+    use std::io::{BufRead, BufReader};
+    use std::path::Path;
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+
+    InitSupervisor::set_subreaper().unwrap();
+
+    // The main child process starts a process in the background, printing its
+    // process id, and exits without waiting for it.
+    #[allow(clippy::zombie_processes)] // Reaped by `InitSupervisor`.
+    let mut child = Command::new("sh")
+        .args(["-c", "sleep 0.2 >/dev/null & echo $!; exit 3"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut supervisor = InitSupervisor::new(&child).unwrap();
+    poll.registry()
+        .register(&mut supervisor, SUPERVISOR, Interest::READABLE)
+        .unwrap();
+
+    let mut line = String::new();
+    let _ = BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let orphan: u32 = line.trim().parse().unwrap();
+
+    let status = wait_for(&mut poll, &mut events, &mut supervisor);
+    assert_eq!(status.code(), Some(3));
+    assert_eq!(supervisor.exit_code(), Some(3));
+
+    // The orphaned process should be adopted, and reaped, by us.
+    let proc_dir = format!("/proc/{}", orphan);
+    if let Ok(stat) = fs::read_to_string(Path::new(&proc_dir).join("stat")) {
+        // Process id, name and state before the parent process id.
+        let ppid = stat.rsplit(')').next().unwrap().split_whitespace().nth(1);
+        assert_eq!(ppid, Some(process::id().to_string().as_str()));
+    }
+    let deadline = Instant::now() + TIMEOUT;
+    while Path::new(&proc_dir).exists() {
+        assert!(Instant::now() < deadline, "orphaned process not reaped");
+        poll.poll(&mut events, Some(Duration::from_millis(100)))
+            .unwrap();
+        // Keeps returning the exit status of the main child process.
+        assert_eq!(supervisor.receive().unwrap(), Some(status));
+    }
+}
+
+/// Wait for the main child process of `supervisor` to exit.
+fn wait_for(poll: &mut Poll, events: &mut Events, supervisor: &mut InitSupervisor) -> ExitStatus {
+    loop {
+        // The child process could have exited before `InitSupervisor` was
+        // created, in which case we won't get an event.
+        if let Some(status) = supervisor.receive().unwrap() {
+            return status;
+        }
+
+        poll.poll(events, Some(TIMEOUT)).unwrap();
+        assert!(!events.is_empty(), "timed out waiting for child process");
+    }
+}
@@ -0,0 +1,92 @@
+//! Tests receiving queued real-time signals in a multi-threaded process.
+
+use std::io;
+use std::time::Instant;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn main() -> io::Result<()> {
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+    use std::{process, thread};
+
+    use mio::{Events, Interest, Poll, Token};
+    use mio_signals::{send_signal, Signal, SignalSet, Signals};
+
+    const SIGNAL: Token = Token(10);
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    let start = Instant::now();
+    println!("\nrunning 1 test");
+
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(8);
+
+    let mut signals = Signals::new(SignalSet::all() | Signal::Realtime(1))?;
+    poll.registry()
+        .register(&mut signals, SIGNAL, Interest::READABLE)?;
+
+    let handles = (0..5)
+        .map(|_| {
+            let (sender, receiver) = channel();
+            let handle = thread::spawn(move || wait_for_msg(receiver));
+            (sender, handle)
+        })
+        .collect::<Vec<_>>();
+
+    // Send ourselves some signals. Real-time signals are queued, so we expect
+    // to receive both of them.
+    let want = vec![Signal::Interrupt, Signal::Realtime(1), Signal::Realtime(1)];
+    for signal in want.iter().copied() {
+        send_signal(process::id(), signal)?;
+    }
+
+    let mut got = Vec::new();
+    while got.len() < want.len() {
+        poll.poll(&mut events, Some(TIMEOUT))?;
+        if events.is_empty() {
+            panic!("failed to get signal event, got signals: {:?}", got);
+        }
+
+        for event in events.iter() {
+            match event.token() {
+                SIGNAL => {
+                    while let Some(signal) = signals.receive()? {
+                        got.push(signal);
+                    }
+                }
+                _ => println!("Got unknown event: {:?}", event),
+            }
+        }
+    }
+    assert_eq!(got, want);
+
+    for (sender, handle) in handles {
+        sender.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    println!("test multi_threaded_realtime ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn wait_for_msg(receiver: std::sync::mpsc::Receiver<()>) {
+    receiver.recv().unwrap();
+}
+
+// Real-time signals are only supported on Android and Linux.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn main() -> io::Result<()> {
+    use mio_signals::{Signal, Signals};
+
+    let start = Instant::now();
+    println!("\nrunning 1 test");
+
+    let err = Signals::new(Signal::Realtime(1).into()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+
+    println!("test multi_threaded_realtime ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Tests for `ProcessHandle` and `ProcessExit`.
+
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+use std::time::Duration;
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{Pid, ProcessExit, ProcessHandle, Signal};
+
+#[test]
+fn send_signal() {
+    let mut child = Command::new("sleep")
+        .arg("10")
+        .spawn()
+        .expect("unable to spawn child process");
+    let handle = ProcessHandle::from_child(&child).expect("unable to create ProcessHandle");
+    handle.send_signal(Signal::Terminate).unwrap();
+    let status = child.wait().expect("unable to wait for child process");
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+}
+
+#[test]
+fn send_signal_after_reap() {
+    let mut child = Command::new("true")
+        .spawn()
+        .expect("unable to spawn child process");
+    let handle = ProcessHandle::from_child(&child).expect("unable to create ProcessHandle");
+    let status = child.wait().expect("unable to wait for child process");
+    assert!(status.success());
+
+    // The process is gone, even if the pid is reused we shouldn't signal it.
+    let err = handle.send_signal(Signal::Terminate).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ESRCH));
+}
+
+#[test]
+fn from_pid_no_process() {
+    // Pid maximum is 2^22, so this process can't exist.
+    let pid = Pid::new(i32::MAX as u32).unwrap();
+    let err = ProcessHandle::from_pid(pid).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ESRCH));
+}
+
+#[test]
+// The child process is reaped by `ProcessExit::exit_status`.
+#[allow(clippy::zombie_processes)]
+fn process_exit_child() {
+    const EXIT: Token = Token(1);
+
+    let mut poll = Poll::new().expect("unable to create Poll");
+    let mut events = Events::with_capacity(8);
+
+    let child = Command::new("sleep")
+        .arg("10")
+        .spawn()
+        .expect("unable to spawn child process");
+    let mut exit = ProcessExit::from_child(&child).expect("unable to create ProcessExit");
+    poll.registry()
+        .register(&mut exit, EXIT, Interest::READABLE)
+        .expect("unable to register ProcessExit");
+
+    assert!(!exit.has_exited().unwrap());
+    assert_eq!(exit.exit_status().unwrap(), None);
+
+    exit.handle().send_signal(Signal::Terminate).unwrap();
+    expect_exit_event(&mut poll, &mut events, EXIT);
+
+    assert!(exit.has_exited().unwrap());
+    let status = exit.exit_status().unwrap().expect("missing exit status");
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+    // Status is kept after the process is reaped.
+    assert_eq!(exit.exit_status().unwrap(), Some(status));
+}
+
+#[test]
+fn process_exit_not_a_child() {
+    const EXIT: Token = Token(1);
+
+    let mut poll = Poll::new().expect("unable to create Poll");
+    let mut events = Events::with_capacity(8);
+
+    // Start a process that isn't our child, by letting the shell start it in
+    // the background.
+    let output = Command::new("sh")
+        .args(["-c", "sleep 1 > /dev/null & echo $!"])
+        .output()
+        .expect("unable to run shell");
+    assert!(output.status.success());
+    let pid: u32 = String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .expect("invalid pid");
+
+    let mut exit = ProcessExit::from_pid(Pid::new(pid).unwrap()).unwrap();
+    poll.registry()
+        .register(&mut exit, EXIT, Interest::READABLE)
+        .expect("unable to register ProcessExit");
+    assert!(!exit.has_exited().unwrap());
+
+    expect_exit_event(&mut poll, &mut events, EXIT);
+    assert!(exit.has_exited().unwrap());
+    // Not our child, so no exit status.
+    assert_eq!(exit.exit_status().unwrap(), None);
+}
+
+fn expect_exit_event(poll: &mut Poll, events: &mut Events, token: Token) {
+    poll.poll(events, Some(Duration::from_secs(5)))
+        .expect("unable to poll");
+    assert!(
+        events.iter().any(|event| event.token() == token),
+        "missing process exit event"
+    );
+}
@@ -0,0 +1,56 @@
+//! Tests for `Signals::new_self_pipe`.
+//!
+//! # Notes
+//!
+//! The self-pipe and the default implementation must not be combined for the
+//! same signal, so this has its own file.
+
+use std::process;
+use std::time::Duration;
+
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{send_signal, Signal, SignalCode, Signals};
+
+#[test]
+fn receive_process_signal() {
+    // The signal can be delivered to any thread that doesn't block it.
+    let mut signals = Signals::new_self_pipe(Signal::User1.into()).unwrap();
+    assert_eq!(signals.receive_info().unwrap(), None);
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(8);
+    poll.registry()
+        .register(&mut signals, Token(0), Interest::READABLE)
+        .unwrap();
+
+    send_signal(process::id(), Signal::User1).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert!(!events.is_empty());
+
+    let info = signals.receive_info().unwrap().unwrap();
+    assert_eq!(info.signal(), Signal::User1);
+    assert_eq!(info.code(), Some(SignalCode::User));
+    assert_eq!(info.pid(), Some(process::id()));
+    assert_eq!(info.uid(), Some(unsafe { libc::getuid() }));
+    assert_eq!(signals.receive_info().unwrap(), None);
+}
+
+#[test]
+fn receive_in_all_signals() {
+    let mut signals1 = Signals::new_self_pipe(Signal::User2.into()).unwrap();
+    let mut signals2 = Signals::new_self_pipe(Signal::User2 | Signal::Hangup).unwrap();
+
+    send_signal(process::id(), Signal::User2).unwrap();
+    let timeout = Duration::from_secs(1);
+    assert_eq!(signals1.wait_timeout(timeout).unwrap(), Some(Signal::User2));
+    assert_eq!(signals2.wait_timeout(timeout).unwrap(), Some(Signal::User2));
+
+    // Only the second `Signals` is interested in this signal.
+    send_signal(process::id(), Signal::Hangup).unwrap();
+    assert_eq!(
+        signals2.wait_timeout(timeout).unwrap(),
+        Some(Signal::Hangup)
+    );
+    assert_eq!(signals1.receive().unwrap(), None);
+}
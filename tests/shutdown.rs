@@ -0,0 +1,75 @@
+//! Tests for `Shutdown`.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mio_signals::{Shutdown, ShutdownState, Signal, Signals};
+
+#[test]
+fn escalate_on_second_signal() {
+    let signals = Signals::new(Signal::User1.into()).expect("unable to create Signals");
+    let mut shutdown = Shutdown::new(signals, Duration::from_secs(60));
+    let handle = shutdown.handle();
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Running);
+    assert_eq!(shutdown.timeout(), None);
+
+    // Send the signal to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Draining);
+    assert_eq!(handle.state(), ShutdownState::Draining);
+    let timeout = shutdown.timeout().expect("missing timeout");
+    assert!(timeout > Duration::from_secs(50) && timeout <= Duration::from_secs(60));
+
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Forced);
+    assert_eq!(handle.state(), ShutdownState::Forced);
+    assert_eq!(shutdown.timeout(), None);
+
+    // Stays forced.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Forced);
+}
+
+#[test]
+fn force_after_grace_period() {
+    const GRACE: Duration = Duration::from_millis(50);
+
+    let signals = Signals::new(Signal::User2.into()).expect("unable to create Signals");
+    let mut shutdown = Shutdown::new(signals, GRACE);
+
+    let handle = shutdown.handle();
+    let waiter = thread::spawn(move || {
+        let state = handle.wait();
+        let start = Instant::now();
+        handle.wait_forced();
+        assert_eq!(handle.state(), ShutdownState::Forced);
+        (state, start.elapsed())
+    });
+
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Draining);
+
+    let (state, elapsed) = waiter.join().unwrap();
+    assert_eq!(state, ShutdownState::Draining);
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Forced);
+}
+
+#[test]
+fn very_long_grace_period() {
+    let signals = Signals::new(Signal::Quit.into()).expect("unable to create Signals");
+    let mut shutdown = Shutdown::new(signals, Duration::MAX);
+
+    let handle = shutdown.handle();
+    let waiter = thread::spawn(move || handle.wait_forced());
+
+    // The grace period is too long to have a deadline, so it never ends.
+    assert_eq!(unsafe { libc::raise(libc::SIGQUIT) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Draining);
+    assert_eq!(shutdown.timeout(), None);
+
+    assert_eq!(unsafe { libc::raise(libc::SIGQUIT) }, 0);
+    assert_eq!(shutdown.receive().unwrap(), ShutdownState::Forced);
+    waiter.join().unwrap();
+}
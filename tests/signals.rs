@@ -1,18 +1,37 @@
 use std::io::Read;
 use std::ops::{Deref, DerefMut};
+use std::os::unix::process::ExitStatusExt;
 use std::process::{Child, Command, Stdio};
-use std::thread::sleep;
-use std::time::Duration;
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
 
-use mio_signals::{send_signal, Signal, SignalSet, Signals};
+use mio::{Events, Interest, Poll, Token};
+use mio_signals::{
+    send_signal, send_signal_to, Pgid, Pid, Signal, SignalInfo, SignalSet, Signals, Target,
+};
 
 #[test]
 fn signal_bit_or() {
     // `Signal` and `Signal` (and `Signal`).
     assert_eq!(
-        Signal::Terminate | Signal::Quit | Signal::Interrupt | Signal::User1 | Signal::User2,
+        Signal::Terminate
+            | Signal::Quit
+            | Signal::Interrupt
+            | Signal::User1
+            | Signal::User2
+            | Signal::Hangup,
         SignalSet::all()
     );
+    assert_eq!(
+        SignalSet::all() | Signal::Child,
+        Signal::Terminate
+            | Signal::Quit
+            | Signal::Interrupt
+            | Signal::User1
+            | Signal::User2
+            | Signal::Hangup
+            | Signal::Child
+    );
     // `Signal` and `SignalSet`.
     assert_eq!(
         Signal::Terminate | SignalSet::from(Signal::Quit),
@@ -43,15 +62,30 @@ fn signal_set() {
     let tests = vec![
         (
             SignalSet::all(),
-            5,
+            6,
             vec![
                 Signal::Interrupt,
                 Signal::Terminate,
                 Signal::Quit,
                 Signal::User1,
                 Signal::User2,
+                Signal::Hangup,
             ],
-            "Interrupt|Quit|Terminate|User1|User2",
+            "Interrupt|Quit|Terminate|User1|User2|Hangup",
+        ),
+        (
+            SignalSet::all() | Signal::Child,
+            7,
+            vec![
+                Signal::Interrupt,
+                Signal::Terminate,
+                Signal::Quit,
+                Signal::User1,
+                Signal::User2,
+                Signal::Hangup,
+                Signal::Child,
+            ],
+            "Interrupt|Quit|Terminate|User1|User2|Hangup|Child",
         ),
         (
             Signal::Interrupt.into(),
@@ -66,6 +100,8 @@ fn signal_set() {
             "Terminate",
         ),
         (Signal::Quit.into(), 1, vec![Signal::Quit], "Quit"),
+        (Signal::Hangup.into(), 1, vec![Signal::Hangup], "Hangup"),
+        (Signal::Child.into(), 1, vec![Signal::Child], "Child"),
         (
             Signal::Interrupt | Signal::Terminate,
             2,
@@ -96,6 +132,28 @@ fn signal_set() {
             vec![Signal::Interrupt, Signal::User1, Signal::User1],
             "Interrupt|User1|User2",
         ),
+        (
+            Signal::Hangup | Signal::Terminate,
+            2,
+            vec![Signal::Hangup, Signal::Terminate],
+            "Terminate|Hangup",
+        ),
+        (
+            Signal::Realtime(0).into(),
+            1,
+            vec![Signal::Realtime(0)],
+            "Realtime(0)",
+        ),
+        (
+            Signal::Interrupt | Signal::Realtime(2) | Signal::Realtime(119),
+            3,
+            vec![
+                Signal::Interrupt,
+                Signal::Realtime(2),
+                Signal::Realtime(119),
+            ],
+            "Interrupt|Realtime(2)|Realtime(119)",
+        ),
     ];
 
     for (set, size, expected, expected_fmt) in tests {
@@ -103,7 +161,7 @@ fn signal_set() {
         assert_eq!(set.len(), size);
 
         // Test `contains`.
-        let mut contains_iter = (&expected).iter().cloned();
+        let mut contains_iter = expected.iter().cloned();
         while let Some(signal) = contains_iter.next() {
             assert!(set.contains(signal));
             assert!(set.contains::<SignalSet>(signal.into()));
@@ -135,9 +193,23 @@ fn signal_set() {
 
 #[test]
 fn signal_set_iter_length() {
-    let set = Signal::Interrupt | Signal::Terminate | Signal::Quit | Signal::User1 | Signal::User2;
+    let set = Signal::Interrupt
+        | Signal::Terminate
+        | Signal::Quit
+        | Signal::User1
+        | Signal::User2
+        | Signal::Hangup
+        | Signal::Child;
     let mut iter = set.into_iter();
 
+    assert!(iter.next().is_some());
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.size_hint(), (6, Some(6)));
+
+    assert!(iter.next().is_some());
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.size_hint(), (5, Some(5)));
+
     assert!(iter.next().is_some());
     assert_eq!(iter.len(), 4);
     assert_eq!(iter.size_hint(), (4, Some(4)));
@@ -161,12 +233,398 @@ fn signal_set_iter_length() {
     assert!(iter.next().is_none());
 }
 
+#[test]
+fn signal_set_realtime() {
+    let set = SignalSet::all() | Signal::Realtime(1);
+    assert_eq!(set.len(), SignalSet::all().len() + 1);
+    assert!(set.contains(Signal::Realtime(1)));
+    assert!(!set.contains(Signal::Realtime(0)));
+    assert!(!SignalSet::all().contains(Signal::Realtime(1)));
+}
+
+#[test]
+fn signal_set_realtime_out_of_range() {
+    let set = SignalSet::from(Signal::Realtime(120));
+    assert_eq!(set, SignalSet::from(Signal::Realtime(255)));
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [Signal::Realtime(119)]);
+    let err = Signals::new(set).unwrap_err();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn new_realtime_out_of_range() {
+    let err = Signals::new(Signal::Realtime(100).into()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = send_signal(std::process::id(), Signal::Realtime(100)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn new_realtime_unsupported() {
+    let err = Signals::new(Signal::Realtime(0).into()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn pid() {
+    assert_eq!(Pid::new(1).unwrap().id(), 1);
+    assert_eq!(Pid::new(i32::MAX as u32).unwrap().id(), i32::MAX as u32);
+    for pid in [0, i32::MAX as u32 + 1, u32::MAX] {
+        let err = Pid::new(pid).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn pgid() {
+    assert_eq!(Pgid::new(2).unwrap().id(), 2);
+    assert_eq!(Pgid::new(i32::MAX as u32).unwrap().id(), i32::MAX as u32);
+    // `1` would be `kill(-1, ..)`, i.e. `Target::AllPermitted`.
+    for pgid in [0, 1, i32::MAX as u32 + 1, u32::MAX] {
+        let err = Pgid::new(pgid).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn send_signal_invalid_pid() {
+    // Used to be cast to `-1`, signalling all processes.
+    let err = send_signal(u32::MAX, Signal::User1).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn send_signal_to_process() {
+    let mut child = Command::new("sleep")
+        .arg("10")
+        .spawn()
+        .expect("unable to spawn child process");
+    let pid = Pid::new(child.id()).unwrap();
+    send_signal_to(Target::Process(pid), Signal::Terminate).unwrap();
+    let status = child.wait().expect("unable to wait for child process");
+    assert_eq!(status.signal(), Some(libc::SIGTERM));
+}
+
 #[test]
 fn receive_no_signal() {
     let mut signals = Signals::new(SignalSet::all()).expect("unable to create Signals");
     assert_eq!(signals.receive().expect("unable to receive signal"), None);
 }
 
+#[test]
+fn receive_info() {
+    let mut signals = Signals::new(Signal::User1.into()).expect("unable to create Signals");
+    assert_eq!(
+        signals.receive_info().expect("unable to receive signal"),
+        None
+    );
+
+    // Send the signal to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+
+    let info = signals
+        .receive_info()
+        .expect("unable to receive signal")
+        .expect("missing signal");
+    assert_eq!(info.signal(), Signal::User1);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        assert_eq!(info.code(), Some(mio_signals::SignalCode::Tkill));
+        assert_eq!(info.pid(), Some(std::process::id()));
+        assert_eq!(info.uid(), Some(unsafe { libc::getuid() }));
+        assert_eq!(info.value(), None);
+        assert_eq!(info.child_status(), None);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        assert_eq!(info.code(), None);
+        assert_eq!(info.pid(), None);
+    }
+    assert_eq!(
+        signals.receive_info().expect("unable to receive signal"),
+        None
+    );
+}
+
+#[test]
+fn receive_without_signal_info() {
+    let mut signals = Signals::builder(Signal::User2.into())
+        .signal_info(false)
+        .build()
+        .expect("unable to create Signals");
+
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+
+    let info = signals
+        .receive_info()
+        .expect("unable to receive signal")
+        .expect("missing signal");
+    assert_eq!(info.signal(), Signal::User2);
+    assert_eq!(info.code(), None);
+    assert_eq!(info.pid(), None);
+    assert_eq!(info.uid(), None);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn ignored_signals() {
+    let signal = Signal::Realtime(6);
+    let raw_signal = libc::SIGRTMIN() + 6;
+    // Ignore the signal, like a process started by `nohup` would for `SIGHUP`.
+    assert_ne!(
+        unsafe { libc::signal(raw_signal, libc::SIG_IGN) },
+        libc::SIG_ERR
+    );
+
+    let err = Signals::new(signal.into()).unwrap_err();
+    assert!(err.to_string().contains("ignored"), "{}", err);
+    let err = Signals::new_fan_out(signal.into()).unwrap_err();
+    assert!(err.to_string().contains("ignored"), "{}", err);
+
+    let mut signals = Signals::builder(signal.into())
+        .reset_ignored(true)
+        .build()
+        .expect("unable to create Signals");
+    assert_eq!(
+        unsafe { libc::signal(raw_signal, libc::SIG_DFL) },
+        libc::SIG_DFL
+    );
+
+    assert_eq!(unsafe { libc::raise(raw_signal) }, 0);
+    assert_eq!(
+        signals.receive().expect("unable to receive signal"),
+        Some(signal)
+    );
+}
+
+#[test]
+fn receive_batch() {
+    let mut signals =
+        Signals::new(Signal::User2 | Signal::Hangup).expect("unable to create Signals");
+    let mut infos = [SignalInfo::new(Signal::Interrupt); 8];
+    assert_eq!(signals.receive_batch(&mut infos).unwrap(), 0);
+
+    // Send the signals to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+
+    let mut got = Vec::new();
+    loop {
+        let n = signals.receive_batch(&mut infos).unwrap();
+        if n == 0 {
+            break;
+        }
+        got.extend(infos[..n].iter().map(|info| info.signal()));
+    }
+    got.sort_unstable();
+    assert_eq!(got, vec![Signal::User2, Signal::Hangup]);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn receive_batch_realtime() {
+    let mut signals = Signals::new(Signal::Realtime(2).into()).expect("unable to create Signals");
+    // Real-time signals are queued, not merged.
+    for _ in 0..5 {
+        assert_eq!(unsafe { libc::raise(libc::SIGRTMIN() + 2) }, 0);
+    }
+
+    let mut infos = [SignalInfo::new(Signal::Interrupt); 3];
+    assert_eq!(signals.receive_batch(&mut infos).unwrap(), 3);
+    assert_eq!(signals.receive_batch(&mut infos).unwrap(), 2);
+    assert_eq!(signals.receive_batch(&mut infos).unwrap(), 0);
+    assert!(infos[..2]
+        .iter()
+        .all(|info| info.signal() == Signal::Realtime(2)));
+    assert_eq!(signals.receive_batch(&mut []).unwrap(), 0);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn drain() {
+    let mut signals = Signals::new(Signal::Realtime(3).into()).expect("unable to create Signals");
+    // More than `Drain` receives at once.
+    for _ in 0..20 {
+        assert_eq!(unsafe { libc::raise(libc::SIGRTMIN() + 3) }, 0);
+    }
+
+    let infos = signals
+        .drain()
+        .collect::<std::io::Result<Vec<_>>>()
+        .expect("unable to receive signals");
+    assert_eq!(infos.len(), 20);
+    assert!(infos
+        .iter()
+        .all(|info| info.signal() == Signal::Realtime(3)));
+    assert_eq!(signals.drain().count(), 0);
+}
+
+#[test]
+fn wait() {
+    let mut signals = Signals::new(Signal::User1.into()).expect("unable to create Signals");
+
+    // Send the signal to this thread, sending it to the process could cause
+    // another thread to receive it.
+    let thread = unsafe { libc::pthread_self() } as usize;
+    let handle = thread::spawn(move || {
+        sleep(Duration::from_millis(50));
+        assert_eq!(
+            unsafe { libc::pthread_kill(thread as libc::pthread_t, libc::SIGUSR1) },
+            0
+        );
+    });
+
+    assert_eq!(
+        signals.wait().expect("unable to wait for signal"),
+        Signal::User1
+    );
+    handle.join().unwrap();
+}
+
+#[test]
+fn wait_timeout() {
+    let mut signals = Signals::new(Signal::Hangup.into()).expect("unable to create Signals");
+
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    assert_eq!(
+        signals
+            .wait_timeout(timeout)
+            .expect("unable to wait for signal"),
+        None
+    );
+    assert!(start.elapsed() >= timeout);
+
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    assert_eq!(
+        signals
+            .wait_timeout(timeout)
+            .expect("unable to wait for signal"),
+        Some(Signal::Hangup)
+    );
+
+    // Timeouts too large to compute a deadline shouldn't panic.
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    assert_eq!(
+        signals
+            .wait_timeout(Duration::MAX)
+            .expect("unable to wait for signal"),
+        Some(Signal::Hangup)
+    );
+}
+
+#[test]
+fn receive_fan_out() {
+    const SIGNALS1: Token = Token(1);
+    const SIGNALS2: Token = Token(2);
+
+    let mut poll = Poll::new().expect("unable to create Poll");
+    let mut events = Events::with_capacity(8);
+
+    let mut signals1 =
+        Signals::new_fan_out(Signal::User2.into()).expect("unable to create Signals");
+    let mut signals2 =
+        Signals::new_fan_out(Signal::User2.into()).expect("unable to create Signals");
+    poll.registry()
+        .register(&mut signals1, SIGNALS1, Interest::READABLE)
+        .expect("unable to register Signals");
+    poll.registry()
+        .register(&mut signals2, SIGNALS2, Interest::READABLE)
+        .expect("unable to register Signals");
+
+    // Send the signal to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+
+    // Both `Signals` should get a copy of the signal.
+    assert_eq!(
+        signals1.receive().expect("unable to receive signal"),
+        Some(Signal::User2)
+    );
+    assert_eq!(signals1.receive().expect("unable to receive signal"), None);
+
+    // Even though the signal was read by the first `Signals`, the second should
+    // still be notified.
+    let mut tokens = Vec::new();
+    while !tokens.contains(&SIGNALS2) {
+        poll.poll(&mut events, Some(Duration::from_secs(1)))
+            .expect("unable to poll");
+        assert!(!events.is_empty(), "missing event for second Signals");
+        tokens.extend(events.iter().map(|event| event.token()));
+    }
+    assert_eq!(
+        signals2.receive().expect("unable to receive signal"),
+        Some(Signal::User2)
+    );
+    assert_eq!(signals2.receive().expect("unable to receive signal"), None);
+
+    // Like the kernel does, multiple copies of a standard signal that aren't
+    // received yet are merged into one.
+    for _ in 0..2 {
+        assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+        assert_eq!(
+            signals1.receive().expect("unable to receive signal"),
+            Some(Signal::User2)
+        );
+    }
+    assert_eq!(
+        signals2.receive().expect("unable to receive signal"),
+        Some(Signal::User2)
+    );
+    assert_eq!(signals2.receive().expect("unable to receive signal"), None);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn check_thread_masks() {
+    use std::sync::mpsc::channel;
+
+    let (tid_sender, tid_receiver) = channel();
+    let (stop_sender, stop_receiver) = channel::<()>();
+    let handle = thread::Builder::new()
+        .name("unblocked".to_owned())
+        .spawn(move || {
+            // Unblock all signals, like a thread spawned by e.g. a C library.
+            unsafe {
+                let mut set: libc::sigset_t = std::mem::zeroed();
+                assert_eq!(libc::sigemptyset(&mut set), 0);
+                assert_eq!(
+                    libc::pthread_sigmask(libc::SIG_SETMASK, &set, std::ptr::null_mut()),
+                    0
+                );
+            }
+            let tid = unsafe { libc::syscall(libc::SYS_gettid) } as u32;
+            tid_sender.send(tid).unwrap();
+            let _ = stop_receiver.recv();
+        })
+        .unwrap();
+    let tid = tid_receiver.recv().unwrap();
+
+    let signals = Signals::new(Signal::Realtime(4).into()).expect("unable to create Signals");
+    let threads = signals
+        .check_thread_masks()
+        .expect("unable to check thread masks");
+
+    let own_tid = unsafe { libc::syscall(libc::SYS_gettid) } as u32;
+    assert!(threads.iter().all(|thread| thread.tid() != own_tid));
+    let thread = threads
+        .iter()
+        .find(|thread| thread.tid() == tid)
+        .expect("missing unblocked thread");
+    assert_eq!(thread.name(), "unblocked");
+    assert_eq!(thread.signals(), Signal::Realtime(4).into());
+
+    drop(stop_sender);
+    handle.join().unwrap();
+}
+
 #[test]
 fn example() {
     let child = run_example("signal_handling");
@@ -174,12 +632,13 @@ fn example() {
     // Give the process some time to startup.
     sleep(Duration::from_millis(200));
 
-    let pid = child.id() as u32;
+    let pid = child.id();
 
     send_signal(pid, Signal::User1).unwrap();
     send_signal(pid, Signal::User2).unwrap();
     send_signal(pid, Signal::Interrupt).unwrap();
     send_signal(pid, Signal::Quit).unwrap();
+    send_signal(pid, Signal::Hangup).unwrap();
     send_signal(pid, Signal::Terminate).unwrap();
 
     let output = read_output(child);
@@ -187,9 +646,9 @@ fn example() {
     // perfectly fine, but does change the output.
     // In the end we do get all signals, which is what we want.
     #[cfg(any(target_os = "linux", target_os = "android"))]
-    let want = format!("Call `kill -s TERM {}` to stop the process\nGot interrupt signal\nGot quit signal\nGot user signal 1\nGot user signal 2\nGot terminate signal\n", pid);
+    let want = format!("Call `kill -s TERM {}` to stop the process\nGot hangup signal\nGot interrupt signal\nGot quit signal\nGot user signal 1\nGot user signal 2\nGot terminate signal\n", pid);
     #[cfg(not(any(target_os = "linux", target_os = "android")))]
-    let want = format!("Call `kill -s TERM {}` to stop the process\nGot user signal 1\nGot user signal 2\nGot interrupt signal\nGot quit signal\nGot terminate signal\n", pid);
+    let want = format!("Call `kill -s TERM {}` to stop the process\nGot user signal 1\nGot user signal 2\nGot interrupt signal\nGot quit signal\nGot hangup signal\nGot terminate signal\n", pid);
     assert_eq!(output, want);
 }
 
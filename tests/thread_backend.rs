@@ -0,0 +1,73 @@
+//! Tests `Backend::Thread`, receiving signals sent to the process.
+//!
+//! # Notes
+//!
+//! This needs to run on its own and thus has its own file, without the test
+//! harness as it spawns threads that could receive the signals.
+
+use std::io;
+use std::time::Instant;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn main() -> io::Result<()> {
+    use std::process;
+    use std::time::Duration;
+
+    use mio::{Events, Interest, Poll, Token};
+    use mio_signals::{send_signal, Backend, Signal, SignalCode, Signals};
+
+    let start = Instant::now();
+    println!("\nrunning 1 test");
+
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(8);
+
+    let mut signals = Signals::builder(Signal::User1 | Signal::Realtime(5))
+        .backend(Backend::Thread)
+        .build()?;
+    poll.registry()
+        .register(&mut signals, Token(0), Interest::READABLE)?;
+
+    // The signals are blocked in this thread, so only the thread of `Signals`
+    // can receive them.
+    send_signal(process::id(), Signal::Realtime(5))?;
+    send_signal(process::id(), Signal::User1)?;
+
+    let mut got = Vec::new();
+    while got.len() < 2 {
+        poll.poll(&mut events, Some(Duration::from_secs(1)))?;
+        if events.is_empty() {
+            panic!("failed to get signal event, got signals: {:?}", got);
+        }
+        while let Some(info) = signals.receive_info()? {
+            assert_eq!(info.code(), Some(SignalCode::User));
+            assert_eq!(info.pid(), Some(process::id()));
+            got.push(info.signal());
+        }
+    }
+    got.sort_by_key(|signal| *signal != Signal::User1);
+    assert_eq!(got, vec![Signal::User1, Signal::Realtime(5)]);
+    drop(signals);
+
+    println!("test thread_backend ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn main() -> io::Result<()> {
+    use mio_signals::{Backend, Signal, Signals};
+
+    let start = Instant::now();
+    println!("\nrunning 1 test");
+
+    let err = Signals::builder(Signal::User1.into())
+        .backend(Backend::Thread)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+
+    println!("test thread_backend ... ok\n");
+    println!("test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in {:?}\n", start.elapsed());
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Tests for `AsyncSignals`.
+
+#![cfg(feature = "tokio")]
+
+use std::future::poll_fn;
+use std::pin::Pin;
+
+use futures_core::Stream;
+use mio_signals::{AsyncSignals, Signal};
+
+// NOTE: these tests use a single threaded runtime, so that the signals are
+// blocked and received in the same thread.
+
+#[tokio::test]
+async fn recv() {
+    let mut signals = AsyncSignals::new(Signal::User1.into()).expect("unable to create Signals");
+
+    // Send the signal to this thread, sending it to the process could cause
+    // another thread to receive it.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+
+    let signal = signals.recv().await.expect("unable to receive signal");
+    assert_eq!(signal, Signal::User1);
+}
+
+#[tokio::test]
+async fn stream() {
+    let mut signals =
+        AsyncSignals::new(Signal::User2 | Signal::Hangup).expect("unable to create Signals");
+
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+
+    let mut got = Vec::new();
+    for _ in 0..2 {
+        let signal = poll_fn(|ctx| Pin::new(&mut signals).poll_next(ctx))
+            .await
+            .expect("stream ended")
+            .expect("unable to receive signal");
+        got.push(signal);
+    }
+    got.sort_unstable();
+    assert_eq!(got, vec![Signal::User2, Signal::Hangup]);
+}